@@ -1,5 +1,7 @@
 use cpal::FromSample;
 use divan::Bencher;
+use rodio::buffer::SamplesBuffer;
+use rodio::source::UniformSourceIterator;
 use rodio::Source;
 
 mod shared;
@@ -19,3 +21,32 @@ fn from_i16_to<T: rodio::Sample + FromSample<i16>>(bencher: Bencher) {
                 .for_each(divan::black_box_drop)
         })
 }
+
+fn music_buffer() -> SamplesBuffer<f32> {
+    let source = TestSource::music_wav().to_f32s();
+    SamplesBuffer::new(
+        source.channels(),
+        source.sample_rate(),
+        source.collect::<Vec<_>>(),
+    )
+}
+
+#[divan::bench]
+fn play_resampled(bencher: Bencher) {
+    let sound = music_buffer();
+    bencher
+        .with_inputs(|| sound.clone())
+        .bench_values(|source| {
+            UniformSourceIterator::<_, f32>::new(source, 2, 48000).for_each(divan::black_box_drop)
+        })
+}
+
+#[divan::bench]
+fn play_prepared(bencher: Bencher) {
+    let sound = music_buffer().prepared_for(2, 48000);
+    bencher
+        .with_inputs(|| sound.clone())
+        .bench_values(|source| {
+            UniformSourceIterator::<_, f32>::new(source, 2, 48000).for_each(divan::black_box_drop)
+        })
+}
@@ -0,0 +1,29 @@
+use std::io::Cursor;
+
+use divan::Bencher;
+use rodio::{Decoder, Source};
+
+fn main() {
+    divan::main();
+}
+
+const MUSIC_MP3: &[u8] = include_bytes!("../assets/music.mp3");
+
+#[divan::bench]
+fn decode_mp3_sample_by_sample(bencher: Bencher) {
+    bencher
+        .with_inputs(|| Decoder::new(Cursor::new(MUSIC_MP3)).unwrap())
+        .bench_values(|source| source.for_each(divan::black_box_drop))
+}
+
+#[divan::bench]
+fn decode_mp3_into_buffer(bencher: Bencher) {
+    bencher
+        .with_inputs(|| Decoder::new(Cursor::new(MUSIC_MP3)).unwrap())
+        .bench_values(|mut source| {
+            let mut buffer = [0i16; 4096];
+            while source.fill_buffer(&mut buffer) == buffer.len() {
+                divan::black_box(&buffer);
+            }
+        })
+}
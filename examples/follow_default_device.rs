@@ -0,0 +1,35 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rodio::source::{SineWave, Source};
+use rodio::{DefaultDeviceWatcher, OutputStream, Sink};
+
+fn main() {
+    let (changed_tx, changed_rx) = mpsc::channel();
+    let _watcher = DefaultDeviceWatcher::new(Duration::from_millis(500), move |name| {
+        let _ = changed_tx.send(name);
+    });
+
+    let (mut _stream, handle) = OutputStream::try_default().unwrap();
+    let mut sink = Sink::try_new(&handle).unwrap();
+    sink.append(SineWave::new(440.0).amplify(0.2));
+    println!("Playing, switch the default output device to move the sound");
+
+    for name in changed_rx.iter().take(3) {
+        println!("Default device is now {name:?}");
+        let position = sink.get_pos();
+
+        // the old stream has to be dropped on the thread that created it
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Could not open the new default device: {err}");
+                continue;
+            }
+        };
+        sink = Sink::try_new(&handle).unwrap();
+        sink.append(SineWave::new(440.0).amplify(0.2));
+        let _ = sink.try_seek(position);
+        _stream = stream;
+    }
+}
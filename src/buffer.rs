@@ -10,15 +10,20 @@
 //! ```
 //!
 
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::source::SeekError;
-use crate::{Sample, Source};
+use crate::source::{SeekError, UniformSourceIterator};
+use crate::{FromSample, Sample, Source};
 
 /// A buffer of samples treated as a source.
+///
+/// Clones share the samples and each have their own position, so the same sound can play
+/// several times at once, or be seeked on one output while it keeps playing on another,
+/// without copying or decoding it again.
 #[derive(Debug, Clone)]
 pub struct SamplesBuffer<S> {
-    data: Vec<S>,
+    data: Arc<[S]>,
     pos: usize,
     channels: u16,
     sample_rate: u32,
@@ -45,7 +50,7 @@ where
         assert!(channels != 0);
         assert!(sample_rate != 0);
 
-        let data = data.into();
+        let data: Vec<S> = data.into();
         let duration_ns = 1_000_000_000u64.checked_mul(data.len() as u64).unwrap()
             / sample_rate as u64
             / channels as u64;
@@ -55,13 +60,49 @@ where
         );
 
         SamplesBuffer {
-            data,
+            data: data.into(),
             pos: 0,
             channels,
             sample_rate,
             duration,
         }
     }
+
+    /// Builds a new `SamplesBuffer` from samples of another type, for example the output of
+    /// a DSP crate, converting each sample with [`FromSample`].
+    ///
+    /// # Panic
+    ///
+    /// Panics in the same cases as [`SamplesBuffer::new`].
+    pub fn from_converted<D, T>(channels: u16, sample_rate: u32, data: D) -> SamplesBuffer<S>
+    where
+        D: IntoIterator<Item = T>,
+        S: FromSample<T>,
+    {
+        let data: Vec<S> = data.into_iter().map(S::from_sample_).collect();
+        SamplesBuffer::new(channels, sample_rate, data)
+    }
+
+    /// Returns the samples that are left converted to `channels` and `sample_rate`, the
+    /// format of an output stream for example.
+    ///
+    /// Mixers and sinks convert every sound to their format while it plays. A sound that is
+    /// played many times, like a sound effect in a game, can be converted once instead:
+    /// keep the prepared buffer and play clones of it, the conversion is then skipped
+    /// because the formats match. Prepare it again if the format of the output changes.
+    /// If the buffer already has the requested format it is only cloned.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `channels` or `sample_rate` is zero.
+    pub fn prepared_for(&self, channels: u16, sample_rate: u32) -> SamplesBuffer<S> {
+        if channels == self.channels && sample_rate == self.sample_rate {
+            return SamplesBuffer::new(channels, sample_rate, &self.data[self.pos..]);
+        }
+        let data: Vec<S> =
+            UniformSourceIterator::new(self.clone(), channels, sample_rate).collect();
+        SamplesBuffer::new(channels, sample_rate, data)
+    }
 }
 
 impl<S> Source for SamplesBuffer<S>
@@ -88,6 +129,20 @@ where
         Some(self.duration)
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        Some(self.data.len() as u64)
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [S]) -> usize {
+        let remaining = &self.data[self.pos..];
+        let len = remaining.len().min(out.len());
+        out[..len].copy_from_slice(&remaining[..len]);
+        self.pos += len;
+        len
+    }
+
     // this is fast because all the samples are in memory already
     // and due to the constant sample_rate we can jump to the right
     // sample directly
@@ -95,6 +150,16 @@ where
     /// This jumps in memory till the sample for `pos`.
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
         let curr_channel = self.pos % self.channels() as usize;
         let new_pos = pos.as_secs_f32() * self.sample_rate() as f32 * self.channels() as f32;
         // saturate pos at the end of the source
@@ -106,7 +171,11 @@ where
         let new_pos = new_pos - curr_channel;
 
         self.pos = new_pos;
-        Ok(())
+
+        let frame = (new_pos / self.channels() as usize) as u64;
+        Ok(Duration::from_nanos(
+            frame * 1_000_000_000 / self.sample_rate() as u64,
+        ))
     }
 }
 
@@ -125,20 +194,62 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.data.len(), Some(self.data.len()))
+        let remaining = self.data.len() - self.pos;
+        (remaining, Some(remaining))
     }
 }
 
+impl<S> ExactSizeIterator for SamplesBuffer<S> where S: Sample {}
+
 #[cfg(test)]
 mod tests {
     use crate::buffer::SamplesBuffer;
-    use crate::source::Source;
+    use crate::source::{Source, UniformSourceIterator};
 
     #[test]
     fn basic() {
         let _ = SamplesBuffer::new(1, 44100, vec![0i16, 0, 0, 0, 0, 0]);
     }
 
+    #[test]
+    fn from_converted() {
+        let buf = SamplesBuffer::<f32>::from_converted(1, 44100, [i16::MIN, 0, 16384]);
+        assert_eq!(buf.collect::<Vec<_>>(), vec![-1.0, 0.0, 0.5]);
+
+        // the 64 bit floats of DSP crates
+        let buf = SamplesBuffer::<i16>::from_converted(1, 44100, vec![0.5f64, -1.0]);
+        assert_eq!(buf.collect::<Vec<_>>(), vec![16384, i16::MIN]);
+    }
+
+    #[test]
+    fn prepared_for() {
+        let buf = SamplesBuffer::new(1, 1000, vec![0.5f32; 100]);
+        let prepared = buf.prepared_for(2, 2000);
+        assert_eq!((prepared.channels(), prepared.sample_rate()), (2, 2000));
+        // the same samples a mixer would convert it to while playing
+        let live: Vec<f32> = UniformSourceIterator::new(buf.clone(), 2, 2000).collect();
+        assert_eq!(prepared.clone().collect::<Vec<_>>(), live);
+
+        let mut buf = buf;
+        buf.nth(9);
+        let same = buf.prepared_for(1, 1000);
+        assert_eq!(same.len(), 90);
+    }
+
+    #[test]
+    fn len_counts_down() {
+        let mut buf = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]);
+        for left in (0..6).rev() {
+            buf.next();
+            assert_eq!(buf.len(), left);
+        }
+        assert_eq!(buf.next(), None);
+        assert_eq!(buf.len(), 0);
+
+        buf.try_seek(std::time::Duration::ZERO).unwrap();
+        assert_eq!(buf.len(), 6);
+    }
+
     #[test]
     #[should_panic]
     fn panic_if_zero_channels() {
@@ -171,6 +282,29 @@ mod tests {
         assert_eq!(buf.next(), None);
     }
 
+    #[test]
+    fn fill_buffer() {
+        let mut buf = SamplesBuffer::new(1, 44100, vec![1i16, 2, 3, 4, 5]);
+        assert_eq!(buf.next(), Some(1));
+
+        let mut out = [0i16; 3];
+        assert_eq!(buf.fill_buffer(&mut out), 3);
+        assert_eq!(out, [2, 3, 4]);
+        assert_eq!(buf.fill_buffer(&mut out), 1);
+        assert_eq!(out[0], 5);
+        assert_eq!(buf.fill_buffer(&mut out), 0);
+    }
+
+    #[test]
+    fn total_len_is_exact() {
+        // 3 samples at 7Hz do not make a whole number of nanoseconds
+        let buf = SamplesBuffer::new(1, 7, vec![0i16; 3]);
+        assert_eq!(buf.total_len(), Some(3));
+
+        let buf = SamplesBuffer::new(2, 44100, vec![0i16; 882]);
+        assert_eq!(buf.total_len(), Some(882));
+    }
+
     #[cfg(test)]
     mod try_seek {
         use super::*;
@@ -197,5 +331,56 @@ mod tests {
             buf.try_seek(Duration::from_secs(6)).unwrap();
             assert!(buf.next().is_some_and(|s| s % 2 == 1),);
         }
+
+        #[test]
+        fn reports_landed_position() {
+            let mut buf = SamplesBuffer::new(2, 100, vec![0i16; 400]);
+            let landed = buf.try_seek_exact(Duration::from_millis(1234)).unwrap();
+            assert_eq!(landed, Duration::from_millis(1230));
+
+            // saturates at the end
+            let landed = buf.try_seek_exact(Duration::from_secs(10)).unwrap();
+            assert_eq!(landed, Duration::from_secs(2));
+            assert_eq!(buf.next(), None);
+        }
+
+        #[test]
+        fn seeks_to_fraction() {
+            let mut buf = SamplesBuffer::new(2, 100, (0..400i16).collect::<Vec<_>>());
+            assert_eq!(buf.try_seek_fraction(0.5).unwrap(), Duration::from_secs(1));
+            assert_eq!(buf.next(), Some(200));
+            assert_eq!(buf.next(), Some(201));
+
+            // rounded to the nearest frame
+            let landed = buf.try_seek_fraction(1.0 / 3.0).unwrap();
+            assert_eq!(landed, Duration::from_millis(670));
+            assert_eq!(buf.next(), Some(134));
+            assert_eq!(buf.next(), Some(135));
+
+            assert_eq!(buf.try_seek_fraction(1.0).unwrap(), Duration::from_secs(2));
+            assert_eq!(buf.next(), None);
+            assert_eq!(buf.try_seek_fraction(7.0).unwrap(), Duration::from_secs(2));
+            assert_eq!(buf.try_seek_fraction(-1.0).unwrap(), Duration::ZERO);
+            assert_eq!(buf.next(), Some(0));
+        }
+
+        #[test]
+        fn seek_fraction_needs_duration() {
+            let mut source = crate::source::from_iter(vec![SamplesBuffer::new(1, 100, vec![0i16])]);
+            assert!(matches!(
+                source.try_seek_fraction(0.5),
+                Err(crate::source::SeekError::UnknownDuration)
+            ));
+        }
+
+        #[test]
+        fn seekable_through_combinators() {
+            let buf = SamplesBuffer::new(1, 100, vec![0i16; 100]);
+            assert!(buf.is_seekable());
+            let source = buf.clone().amplify(0.5).speed(2.0).skippable();
+            assert!(source.is_seekable());
+            // buffered sources can not seek
+            assert!(!buf.buffered().amplify(0.5).is_seekable());
+        }
     }
 }
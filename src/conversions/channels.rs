@@ -1,6 +1,10 @@
 use cpal::Sample;
 
 /// Iterator that converts from a certain channel count to another.
+///
+/// Extra channels are dropped and missing ones are filled with the first channel, then
+/// silence. Converting to a single channel averages all channels instead, so the mono output
+/// of a stereo source contains both sides.
 #[derive(Clone, Debug)]
 pub struct ChannelCountConverter<I>
 where
@@ -47,6 +51,12 @@ where
         self.input
     }
 
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// Get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
@@ -54,6 +64,27 @@ where
     }
 }
 
+impl<I> ChannelCountConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    /// Returns the average of the channels of the next input frame.
+    fn next_downmixed(&mut self) -> Option<I::Item> {
+        let mut sum = self.input.next()?.to_float_sample();
+        let mut count = 1;
+        for _ in 1..self.from {
+            let Some(value) = self.input.next() else {
+                break;
+            };
+            sum = sum + value.to_float_sample();
+            count += 1;
+        }
+        let scale = <I::Item as Sample>::Float::from_sample(1.0 / count as f32);
+        Some((sum * scale).to_sample())
+    }
+}
+
 impl<I> Iterator for ChannelCountConverter<I>
 where
     I: Iterator,
@@ -62,6 +93,10 @@ where
     type Item = I::Item;
 
     fn next(&mut self) -> Option<I::Item> {
+        if self.to == 1 && self.from > 1 {
+            return self.next_downmixed();
+        }
+
         let result = match self.next_output_sample_pos {
             0 => {
                 // save first sample for mono -> stereo conversion
@@ -96,9 +131,9 @@ where
         let (min, max) = self.input.size_hint();
 
         let consumed = std::cmp::min(self.from, self.next_output_sample_pos) as usize;
-        let calculate = |size| {
-            (size + consumed) / self.from as usize * self.to as usize
-                - self.next_output_sample_pos as usize
+        let calculate = |size: usize| {
+            ((size + consumed) / self.from as usize * self.to as usize)
+                .saturating_sub(self.next_output_sample_pos as usize)
         };
 
         let min = calculate(min);
@@ -126,8 +161,19 @@ mod test {
         assert_eq!(output, [1, 2, 4, 5]);
 
         let input = vec![1u16, 2, 3, 4, 5, 6, 7, 8];
+        let output = ChannelCountConverter::new(input.into_iter(), 4, 2).collect::<Vec<_>>();
+        assert_eq!(output, [1, 2, 5, 6]);
+    }
+
+    #[test]
+    fn downmix_to_mono() {
+        let input = vec![0.5f32, -0.5, 1.0, 0.0];
+        let output = ChannelCountConverter::new(input.into_iter(), 2, 1).collect::<Vec<_>>();
+        assert_eq!(output, [0.0, 0.5]);
+
+        let input = vec![100i16, 200, 300, 400, 500, 600, 700, 800];
         let output = ChannelCountConverter::new(input.into_iter(), 4, 1).collect::<Vec<_>>();
-        assert_eq!(output, [1, 5]);
+        assert_eq!(output, [250, 650]);
     }
 
     #[test]
@@ -7,7 +7,8 @@ This includes conversion between sample formats, channels or sample rates.
 
 pub use self::channels::ChannelCountConverter;
 pub use self::sample::DataConverter;
-pub use self::sample::Sample;
+pub use self::sample::{FromSample, Sample};
+pub(crate) use self::sample_rate::AntiAlias;
 pub use self::sample_rate::SampleRateConverter;
 
 mod channels;
@@ -1,4 +1,5 @@
-use cpal::{FromSample, Sample as CpalSample};
+pub use cpal::FromSample;
+use cpal::Sample as CpalSample;
 use std::marker::PhantomData;
 
 /// Converts the samples data type to `O`.
@@ -24,6 +25,12 @@ impl<I, O> DataConverter<I, O> {
         self.input
     }
 
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
@@ -69,8 +76,26 @@ where
 /// - For `f32`, silence corresponds to the value `0.0`. The minimum and maximum amplitudes are
 ///   represented by `-1.0` and `1.0` respectively.
 ///
-/// You can implement this trait on your own type as well if you wish so.
+/// Conversions between sample types go through [`FromSample`] and the `to_sample` method of
+/// [`cpal::Sample`], from the `dasp_sample` crate that cpal builds on. The three types above
+/// implement them for each other and for the other types of `dasp_sample`, such as `i32`
+/// and `f64`, so samples from DSP crates built on `dasp` convert directly, for example with
+/// [`Source::convert_samples`](crate::Source::convert_samples) or
+/// [`SamplesBuffer::from_converted`](crate::buffer::SamplesBuffer::from_converted).
+/// Converting `i16` or `u16` to `f32` and back, and between `i16` and `u16`, is lossless.
 ///
+/// # Implementing `Sample`
+///
+/// You can implement this trait on your own type as well. The type must first implement
+/// [`cpal::Sample`], which is `dasp_sample::Sample`, and so define its conversions to and
+/// from a signed and a floating point sample type. Then:
+///
+/// - `zero_value` must return the same value as `cpal::Sample::EQUILIBRIUM`.
+/// - `lerp` must work for any `numerator` up to `denominator`, without overflowing for
+///   large values of either.
+/// - `amplify` scales the distance to silence, an amplification of `1.0` returns the
+///   sample unchanged.
+/// - `saturating_add` clamps to the range of the type rather than wrapping.
 pub trait Sample: CpalSample {
     /// Linear interpolation between two samples.
     ///
@@ -90,10 +115,11 @@ pub trait Sample: CpalSample {
 impl Sample for u16 {
     #[inline]
     fn lerp(first: u16, second: u16, numerator: u32, denominator: u32) -> u16 {
-        let a = first as i32;
-        let b = second as i32;
-        let n = numerator as i32;
-        let d = denominator as i32;
+        // 64 bit, the difference times a large numerator does not fit in 32 bits
+        let a = first as i64;
+        let b = second as i64;
+        let n = numerator as i64;
+        let d = denominator as i64;
         (a + (b - a) * n / d) as u16
     }
 
@@ -116,7 +142,7 @@ impl Sample for u16 {
 impl Sample for i16 {
     #[inline]
     fn lerp(first: i16, second: i16, numerator: u32, denominator: u32) -> i16 {
-        (first as i32 + (second as i32 - first as i32) * numerator as i32 / denominator as i32)
+        (first as i64 + (second as i64 - first as i64) * numerator as i64 / denominator as i64)
             as i16
     }
 
@@ -157,3 +183,20 @@ impl Sample for f32 {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CpalSample;
+
+    #[test]
+    fn integer_to_float_is_lossless() {
+        for sample in i16::MIN..=i16::MAX {
+            let float: f32 = sample.to_sample();
+            assert_eq!(i16::from_sample(float), sample);
+
+            let unsigned: u16 = sample.to_sample();
+            assert_eq!(i16::from_sample(unsigned), sample);
+            assert_eq!(u16::from_sample(unsigned.to_sample::<f32>()), unsigned);
+        }
+    }
+}
@@ -1,7 +1,61 @@
 use crate::conversions::Sample;
 
+use std::f64::consts::PI;
 use std::mem;
 
+/// Low pass filter for the input of a heavy downsampling, so frequencies the lower sample rate
+/// can not represent are removed instead of folding back as aliasing.
+///
+/// Two cascaded one pole filters with their cutoff at the new Nyquist frequency. They are
+/// built on `Sample::lerp`, so they work for every sample type.
+#[derive(Clone, Debug)]
+pub(crate) struct AntiAlias<S> {
+    // The coefficient of both poles, out of `ANTI_ALIAS_SCALE`.
+    coefficient: u32,
+    // The state of both poles for every channel, filled in by the first frame.
+    state: Vec<[S; 2]>,
+    channels: usize,
+    // The channel of the next sample.
+    channel: usize,
+}
+
+const ANTI_ALIAS_SCALE: u32 = 1 << 16;
+
+impl<S> AntiAlias<S>
+where
+    S: Sample,
+{
+    /// Returns a filter when converting `from` to `to` is downsampling by a factor of two or
+    /// more. Linear interpolation is left to handle milder ratios on its own.
+    pub(crate) fn new(from: u32, to: u32, channels: u16) -> Option<AntiAlias<S>> {
+        if to == 0 || from / to < 2 {
+            return None;
+        }
+        let coefficient = 1.0 - (-PI * to as f64 / from as f64).exp();
+        Some(AntiAlias {
+            coefficient: ((coefficient * ANTI_ALIAS_SCALE as f64).round() as u32).max(1),
+            state: Vec::with_capacity(channels as usize),
+            channels: channels.max(1) as usize,
+            channel: 0,
+        })
+    }
+
+    /// Filters the next sample, the samples are interleaved.
+    #[inline]
+    pub(crate) fn filter(&mut self, sample: S) -> S {
+        let channel = self.channel;
+        self.channel = (channel + 1) % self.channels;
+        let Some([first, second]) = self.state.get_mut(channel) else {
+            // start from the first frame instead of from silence
+            self.state.push([sample; 2]);
+            return sample;
+        };
+        *first = S::lerp(*first, sample, self.coefficient, ANTI_ALIAS_SCALE);
+        *second = S::lerp(*second, *first, self.coefficient, ANTI_ALIAS_SCALE);
+        *second
+    }
+}
+
 /// Iterator that converts from a certain sample rate to another.
 #[derive(Clone, Debug)]
 pub struct SampleRateConverter<I>
@@ -102,6 +156,12 @@ where
         self.input
     }
 
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
@@ -156,8 +216,10 @@ where
             self.current_frame_pos_in_chunk = 0;
         } else {
             // Finding the position of the first sample of the linear interpolation.
-            let req_left_sample =
-                (self.from * self.next_output_frame_pos_in_chunk / self.to) % self.from;
+            // 64 bit, the product overflows for large coprime rates like 44101 and 192000
+            let req_left_sample = (self.from as u64 * self.next_output_frame_pos_in_chunk as u64
+                / self.to as u64) as u32
+                % self.from;
 
             // Advancing `self.current_frame`, `self.next_frame` and
             // `self.current_frame_pos_in_chunk` until the latter variable
@@ -172,7 +234,8 @@ where
         // Note that `self.output_buffer` can be truncated if there is not enough data in
         // `self.next_frame`.
         let mut result = None;
-        let numerator = (self.from * self.next_output_frame_pos_in_chunk) % self.to;
+        let numerator =
+            (self.from as u64 * self.next_output_frame_pos_in_chunk as u64 % self.to as u64) as u32;
         for (off, (cur, next)) in self
             .current_frame
             .iter()
@@ -374,4 +437,15 @@ mod test {
         let output = output.collect::<Vec<_>>();
         assert_eq!(output, [2, 16, 3, 17, 4, 18, 6, 20, 7, 21, 8, 22]);
     }
+
+    #[test]
+    fn large_coprime_rates() {
+        // the interpolation position times either rate does not fit in 32 bits
+        let input = [i16::MIN, i16::MAX].repeat(44101);
+        let output =
+            SampleRateConverter::new(input.into_iter(), SampleRate(44101), SampleRate(192000), 2);
+        let output: Vec<i16> = output.collect();
+        assert!(output.len().abs_diff(2 * 192000) <= 8, "{}", output.len());
+        assert!(output.chunks(2).all(|frame| frame == [i16::MIN, i16::MAX]));
+    }
 }
@@ -0,0 +1,219 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::buffer::SamplesBuffer;
+use crate::dynamic_mixer::{DynamicMixerController, OneShotHandle};
+use crate::stream::{OutputStreamHandle, PlayError};
+use crate::{Sample, Source};
+
+/// Previews a sound from a cue point on its own output, like the headphones of a DJ, while
+/// the sound keeps playing elsewhere.
+///
+/// The player holds a [`SamplesBuffer`], whose clones share their samples. Play a clone of
+/// the same buffer on the main output and give the buffer to a `CuePlayer` on a second
+/// output: the sound is decoded once and the two playheads are independent, seeking the
+/// preview does not move the main playback and the other way around.
+///
+/// # Clock drift
+///
+/// Every output device plays at its own clock. Two devices set to the same sample rate still
+/// differ by a few samples per million, so a preview started at the position of the main
+/// playback slowly drifts away from it, in the order of milliseconds per minute. Neither
+/// playhead is corrected for that. To keep a preview in sync with the main playback, restart
+/// it now and then with [`preview_from`](CuePlayer::preview_from) at the current position of
+/// the main playback, for example [`Sink::get_pos`](crate::Sink::get_pos).
+///
+/// ```no_run
+/// use rodio::buffer::SamplesBuffer;
+/// use rodio::{CuePlayer, OutputStream, Sink};
+/// use std::time::Duration;
+///
+/// let (_main_stream, main) = OutputStream::try_default().unwrap();
+/// // the headphones, usually another device, see `OutputStream::try_from_device`
+/// let (_cue_stream, cue) = OutputStream::try_default().unwrap();
+///
+/// let track = SamplesBuffer::new(2, 44100, vec![0i16; 44100 * 60]);
+/// let deck = Sink::try_new(&main).unwrap();
+/// deck.append(track.clone());
+///
+/// let mut preview = CuePlayer::try_new(track, &cue).unwrap();
+/// preview.set_cue_point(Duration::from_secs(30));
+/// preview.preview().unwrap();
+/// ```
+pub struct CuePlayer<S> {
+    sound: SamplesBuffer<S>,
+    mixer: Weak<DynamicMixerController<f32>>,
+    cue_point: Duration,
+    volume: f32,
+    current: Option<OneShotHandle>,
+}
+
+impl<S> CuePlayer<S>
+where
+    S: Sample + Send + Sync + 'static,
+    f32: FromSample<S>,
+{
+    /// Builds a new `CuePlayer` that previews `sound` on an output stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NoDevice`] if the output stream was dropped.
+    pub fn try_new(
+        sound: SamplesBuffer<S>,
+        output: &OutputStreamHandle,
+    ) -> Result<CuePlayer<S>, PlayError> {
+        let mixer = output.mixer();
+        if mixer.upgrade().is_none() {
+            return Err(PlayError::NoDevice);
+        }
+        Ok(CuePlayer::with_mixer(sound, mixer.clone()))
+    }
+
+    /// Builds a new `CuePlayer` that previews `sound` into `mixer` instead of an output
+    /// device, see [`Sink::connect_new`](crate::Sink::connect_new).
+    #[inline]
+    pub fn connect_new(
+        sound: SamplesBuffer<S>,
+        mixer: &Arc<DynamicMixerController<f32>>,
+    ) -> CuePlayer<S> {
+        CuePlayer::with_mixer(sound, Arc::downgrade(mixer))
+    }
+
+    fn with_mixer(
+        sound: SamplesBuffer<S>,
+        mixer: Weak<DynamicMixerController<f32>>,
+    ) -> CuePlayer<S> {
+        CuePlayer {
+            sound,
+            mixer,
+            cue_point: Duration::ZERO,
+            volume: 1.0,
+            current: None,
+        }
+    }
+
+    /// Returns the sound that is previewed.
+    #[inline]
+    pub fn sound(&self) -> &SamplesBuffer<S> {
+        &self.sound
+    }
+
+    /// Returns the position [`preview`](CuePlayer::preview) starts at.
+    #[inline]
+    pub fn cue_point(&self) -> Duration {
+        self.cue_point
+    }
+
+    /// Changes the position [`preview`](CuePlayer::preview) starts at. Does not affect a
+    /// preview that is playing.
+    #[inline]
+    pub fn set_cue_point(&mut self, pos: Duration) {
+        self.cue_point = pos;
+    }
+
+    /// Plays the sound from the cue point, see [`preview_from`](CuePlayer::preview_from).
+    #[inline]
+    pub fn preview(&mut self) -> Result<(), PlayError> {
+        self.preview_from(self.cue_point)
+    }
+
+    /// Plays the sound from `pos`, sample accurate. A preview that is playing stops first,
+    /// seeking a preview is previewing again from another position.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlayError::NoDevice`] if the output stream or mixer was dropped.
+    pub fn preview_from(&mut self, pos: Duration) -> Result<(), PlayError> {
+        self.stop();
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        let mut sound = self.sound.clone();
+        sound.try_seek_exact(pos).expect("buffers can always seek");
+        let handle = mixer.try_add_oneshot(sound.convert_samples::<f32>())?;
+        handle.set_volume(self.volume);
+        self.current = Some(handle);
+        Ok(())
+    }
+
+    /// Stops the preview, if one is playing.
+    #[inline]
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.current.take() {
+            handle.stop();
+        }
+    }
+
+    /// Returns true while a preview plays.
+    #[inline]
+    pub fn is_previewing(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Returns the volume of the preview, `1.0` is the original volume of the sound.
+    #[inline]
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Changes the volume of the preview, also of one that is playing.
+    #[inline]
+    pub fn set_volume(&mut self, value: f32) {
+        self.volume = value;
+        if let Some(handle) = &self.current {
+            handle.set_volume(value);
+        }
+    }
+}
+
+impl<S> Drop for CuePlayer<S> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(handle) = self.current.take() {
+            handle.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::CuePlayer;
+
+    #[test]
+    fn playheads_are_independent() {
+        let track = SamplesBuffer::new(1, 1000, (0..20).map(|n| n as f32).collect::<Vec<_>>());
+        let (main, mut main_out) = dynamic_mixer::mixer::<f32>(1, 1000);
+        let (cue, mut cue_out) = dynamic_mixer::mixer::<f32>(1, 1000);
+        main.add(track.clone());
+        assert_eq!(
+            main_out.by_ref().take(3).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0]
+        );
+
+        let mut preview = CuePlayer::connect_new(track, &cue);
+        preview.set_cue_point(Duration::from_millis(10));
+        preview.preview().unwrap();
+        assert!(preview.is_previewing());
+        assert_eq!(
+            cue_out.by_ref().take(2).collect::<Vec<_>>(),
+            vec![10.0, 11.0]
+        );
+
+        // seeking the preview leaves the main playback where it was
+        preview.preview_from(Duration::from_millis(5)).unwrap();
+        assert_eq!(cue_out.next(), Some(5.0));
+        assert_eq!(main_out.next(), Some(3.0));
+
+        preview.set_volume(0.5);
+        assert_eq!(cue_out.next(), Some(3.0));
+        preview.stop();
+        assert_eq!(cue_out.next(), None);
+        assert!(!preview.is_previewing());
+    }
+}
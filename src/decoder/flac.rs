@@ -6,6 +6,8 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::{DecodeErrors, Metadata};
+
 use claxon::FlacReader;
 
 /// Decoder for the Flac format.
@@ -21,6 +23,7 @@ where
     sample_rate: u32,
     channels: u16,
     samples: Option<u64>,
+    errors: DecodeErrors,
 }
 
 impl<R> FlacDecoder<R>
@@ -47,11 +50,60 @@ where
             sample_rate: spec.sample_rate,
             channels: spec.channels as u16,
             samples: spec.samples,
+            errors: DecodeErrors::default(),
         })
     }
+    pub fn bits_per_sample(&self) -> u32 {
+        self.bits_per_sample
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        Metadata::from_vorbis_comments(self.reader.tags())
+    }
+
     pub fn into_inner(self) -> R {
         self.reader.into_inner()
     }
+
+    pub(crate) fn set_error_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        self.errors.set_callback(callback);
+    }
+
+    /// Loads the next block if the current one has been played. Returns `None` once there is
+    /// nothing left to decode.
+    fn refill(&mut self) -> Option<()> {
+        while self.current_block_off >= self.current_block.len() {
+            self.current_block_off = 0;
+            let buffer = mem::take(&mut self.current_block);
+            // claxon can not resync after a bad frame, so any error ends the source
+            match self.reader.blocks().read_next_or_eof(buffer) {
+                Ok(Some(block)) if block.channels() == self.channels as u32 => {
+                    self.current_block_channel_len = (block.len() / block.channels()) as usize;
+                    self.current_block = block.into_buffer();
+                }
+                Ok(None) => return None,
+                _ => {
+                    self.errors.report();
+                    return None;
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Returns the sample at interleaved position `off` of the current block, which stores
+    /// its channels one after the other.
+    #[inline]
+    fn block_sample(&self, off: usize) -> i16 {
+        let real_offset = (off % self.channels as usize) * self.current_block_channel_len
+            + off / self.channels as usize;
+        let raw_val = self.current_block[real_offset];
+        match self.bits_per_sample.cmp(&16) {
+            Ordering::Less => (raw_val << (16 - self.bits_per_sample)) as i16,
+            Ordering::Equal => raw_val as i16,
+            Ordering::Greater => (raw_val >> (self.bits_per_sample - 16)) as i16,
+        }
+    }
 }
 
 impl<R> Source for FlacDecoder<R>
@@ -81,6 +133,25 @@ where
             .map(|s| Duration::from_micros(s * 1_000_000 / self.sample_rate as u64))
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.samples.map(|frames| frames * self.channels as u64)
+    }
+
+    /// Interleaves whole blocks at once instead of loading them one sample at a time.
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        let mut filled = 0;
+        while filled < out.len() && self.refill().is_some() {
+            let len = (self.current_block.len() - self.current_block_off).min(out.len() - filled);
+            for slot in &mut out[filled..filled + len] {
+                *slot = self.block_sample(self.current_block_off);
+                self.current_block_off += 1;
+            }
+            filled += len;
+        }
+        filled
+    }
+
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
         Err(SeekError::NotSupported {
@@ -97,33 +168,10 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<i16> {
-        loop {
-            if self.current_block_off < self.current_block.len() {
-                // Read from current block.
-                let real_offset = (self.current_block_off % self.channels as usize)
-                    * self.current_block_channel_len
-                    + self.current_block_off / self.channels as usize;
-                let raw_val = self.current_block[real_offset];
-                self.current_block_off += 1;
-                let real_val = match self.bits_per_sample.cmp(&16) {
-                    Ordering::Less => (raw_val << (16 - self.bits_per_sample)) as i16,
-                    Ordering::Equal => raw_val as i16,
-                    Ordering::Greater => (raw_val >> (self.bits_per_sample - 16)) as i16,
-                };
-                return Some(real_val);
-            }
-
-            // Load the next block.
-            self.current_block_off = 0;
-            let buffer = mem::take(&mut self.current_block);
-            match self.reader.blocks().read_next_or_eof(buffer) {
-                Ok(Some(block)) => {
-                    self.current_block_channel_len = (block.len() / block.channels()) as usize;
-                    self.current_block = block.into_buffer();
-                }
-                _ => return None,
-            }
-        }
+        self.refill()?;
+        let sample = self.block_sample(self.current_block_off);
+        self.current_block_off += 1;
+        Some(sample)
     }
 }
 
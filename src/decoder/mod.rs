@@ -3,7 +3,7 @@
 use std::error::Error;
 use std::fmt;
 #[allow(unused_imports)]
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::mem;
 use std::str::FromStr;
 use std::time::Duration;
@@ -30,6 +30,69 @@ mod vorbis;
 #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
 mod wav;
 
+/// Keeps count of the frames a decoder skipped because they could not be decoded.
+// unused when the wav decoder is the only one enabled
+#[allow(dead_code)]
+#[derive(Default)]
+pub(crate) struct DecodeErrors {
+    count: usize,
+    callback: Option<Box<dyn FnMut(usize) + Send>>,
+}
+
+#[allow(dead_code)]
+impl DecodeErrors {
+    /// Records a skipped frame and calls the callback with the new total.
+    pub(crate) fn report(&mut self) {
+        self.count += 1;
+        if let Some(callback) = &mut self.callback {
+            callback(self.count);
+        }
+    }
+
+    pub(crate) fn set_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        self.callback = Some(callback);
+    }
+}
+
+/// Tags found in a file while it was being decoded.
+///
+/// Reading tags is best effort, not every format or decoder supports it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// Title of the track.
+    pub title: Option<String>,
+    /// Artist of the track.
+    pub artist: Option<String>,
+    /// Album the track is on.
+    pub album: Option<String>,
+    /// Every tag as a key and value pair, including the ones above. The keys are in the
+    /// form the file stores them, for example `TITLE` in a Vorbis comment or `TIT2` in ID3.
+    pub tags: Vec<(String, String)>,
+}
+
+impl Metadata {
+    #[cfg(any(
+        all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+        all(feature = "flac", not(feature = "symphonia-flac"))
+    ))]
+    fn from_vorbis_comments<'a>(comments: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut metadata = Metadata::default();
+        for (key, value) in comments {
+            let field = match key.to_ascii_uppercase().as_str() {
+                "TITLE" => Some(&mut metadata.title),
+                "ARTIST" => Some(&mut metadata.artist),
+                "ALBUM" => Some(&mut metadata.album),
+                _ => None,
+            };
+            if let Some(field) = field {
+                field.get_or_insert_with(|| value.to_owned());
+            }
+            metadata.tags.push((key.to_owned(), value.to_owned()));
+        }
+        metadata
+    }
+}
+
 /// Source of audio samples from decoding a file.
 ///
 /// Supports MP3, WAV, Vorbis and Flac.
@@ -83,6 +146,23 @@ impl<R: Read + Seek> DecoderImpl<R> {
         }
     }
 
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.fill_buffer(out),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.fill_buffer(out),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.fill_buffer(out),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.fill_buffer(out),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.fill_buffer(out),
+            DecoderImpl::None(_) => 0,
+        }
+    }
+
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         match self {
@@ -168,6 +248,23 @@ impl<R: Read + Seek> DecoderImpl<R> {
         }
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.total_len(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.total_len(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.total_len(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.total_len(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.total_len(),
+            DecoderImpl::None(_) => Some(0),
+        }
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         match self {
@@ -186,6 +283,109 @@ impl<R: Read + Seek> DecoderImpl<R> {
             }),
         }
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.try_seek_exact(pos),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.try_seek_exact(pos),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.try_seek_exact(pos),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.try_seek_exact(pos),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.try_seek_exact(pos),
+            DecoderImpl::None(_) => Err(SeekError::NotSupported {
+                underlying_source: "DecoderImpl::None",
+            }),
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.is_seekable(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.is_seekable(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.is_seekable(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.is_seekable(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.is_seekable(),
+            DecoderImpl::None(_) => false,
+        }
+    }
+
+    #[inline]
+    fn codec(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(_) => Some("pcm"),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(_) => Some("vorbis"),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(_) => Some("flac"),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(_) => Some("mp3"),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.codec(),
+            DecoderImpl::None(_) => None,
+        }
+    }
+
+    #[inline]
+    fn bits_per_sample(&self) -> Option<u32> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => Some(source.bits_per_sample()),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(_) => None,
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => Some(source.bits_per_sample()),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(_) => None,
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.bits_per_sample(),
+            DecoderImpl::None(_) => None,
+        }
+    }
+
+    fn metadata(&self) -> Metadata {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(_) => Metadata::default(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.metadata(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.metadata(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(_) => Metadata::default(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.metadata().clone(),
+            DecoderImpl::None(_) => Metadata::default(),
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn set_error_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(_) => (),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.set_error_callback(callback),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.set_error_callback(callback),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.set_error_callback(callback),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.set_error_callback(callback),
+            DecoderImpl::None(_) => (),
+        }
+    }
 }
 
 impl<R> Decoder<R>
@@ -195,8 +395,74 @@ where
     /// Builds a new decoder.
     ///
     /// Attempts to automatically detect the format of the source of data.
-    #[allow(unused_variables)]
     pub fn new(data: R) -> Result<Decoder<R>, DecoderError> {
+        Self::new_with_gapless(data, true)
+    }
+
+    /// Builds a new decoder, choosing whether to play gapless.
+    ///
+    /// With `gapless` set the encoder delay and padding stored in the file, for example in
+    /// the LAME header of an MP3, are trimmed off. Consecutive tracks then play without
+    /// silence in between. Pass `false` to get the raw decoded stream instead. Formats
+    /// without such metadata, or decoded without symphonia, ignore the flag.
+    ///
+    /// Attempts to automatically detect the format of the source of data.
+    pub fn new_with_gapless(data: R, gapless: bool) -> Result<Decoder<R>, DecoderError> {
+        Self::probe(data, None, gapless)
+    }
+
+    /// Builds a new decoder, trying the format given by `hint` first.
+    ///
+    /// The hint is a file extension like `"mp3"` or `".flac"`, or a MIME type like
+    /// `"audio/ogg"`. It saves probing and resolves ambiguous data, which helps when
+    /// loading many sounds from memory, for example a `Cursor` over an asset pack. If the
+    /// data turns out to be in another format, all formats are probed as in
+    /// [`Decoder::new`].
+    pub fn new_with_hint(data: R, hint: &str) -> Result<Decoder<R>, DecoderError> {
+        let hint = extension_from_hint(hint);
+
+        #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+        let data = match hint.as_str() {
+            "wav" | "wave" => match wav::WavDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => return Ok(Decoder(DecoderImpl::Wav(decoder))),
+            },
+            _ => data,
+        };
+
+        #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+        let data = match hint.as_str() {
+            "flac" => match flac::FlacDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => return Ok(Decoder(DecoderImpl::Flac(decoder))),
+            },
+            _ => data,
+        };
+
+        #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+        let data = match hint.as_str() {
+            "ogg" | "oga" => match vorbis::VorbisDecoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => return Ok(Decoder(DecoderImpl::Vorbis(decoder))),
+            },
+            _ => data,
+        };
+
+        #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+        let data = match hint.as_str() {
+            "mp3" => match mp3::Mp3Decoder::new(data) {
+                Err(data) => data,
+                Ok(decoder) => return Ok(Decoder(DecoderImpl::Mp3(decoder))),
+            },
+            _ => data,
+        };
+
+        // symphonia probes every format it knows, the hint only decides which goes first
+        Self::probe(data, Some(&hint), true)
+    }
+
+    #[allow(unused_variables)]
+    fn probe(data: R, hint: Option<&str>, gapless: bool) -> Result<Decoder<R>, DecoderError> {
         #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
         let data = match wav::WavDecoder::new(data) {
             Err(data) => data,
@@ -236,7 +502,7 @@ where
                 Default::default(),
             );
 
-            match symphonia::SymphoniaDecoder::new(mss, None) {
+            match symphonia::SymphoniaDecoder::new(mss, hint, gapless) {
                 Err(e) => Err(e),
                 Ok(decoder) => Ok(Decoder(DecoderImpl::Symphonia(decoder))),
             }
@@ -331,13 +597,99 @@ where
             Default::default(),
         );
 
-        match symphonia::SymphoniaDecoder::new(mss, Some(hint)) {
+        match symphonia::SymphoniaDecoder::new(mss, Some(hint), true) {
             Err(e) => Err(e),
             Ok(decoder) => Ok(Decoder(DecoderImpl::Symphonia(decoder))),
         }
     }
 }
 
+/// Turns a file extension or MIME type into a lowercase extension without leading dot.
+fn extension_from_hint(hint: &str) -> String {
+    let hint = hint.trim().trim_start_matches('.').to_ascii_lowercase();
+    let Some((_, subtype)) = hint.split_once('/') else {
+        return hint;
+    };
+    match subtype {
+        "wav" | "wave" | "x-wav" | "vnd.wave" => "wav",
+        "flac" | "x-flac" => "flac",
+        "ogg" | "vorbis" | "x-vorbis+ogg" => "ogg",
+        "mpeg" | "mp3" | "mpeg3" | "x-mpeg-3" => "mp3",
+        "aac" | "x-aac" => "aac",
+        "mp4" | "m4a" | "x-m4a" => "m4a",
+        other => other.trim_start_matches("x-"),
+    }
+    .to_owned()
+}
+
+impl Decoder<Cursor<&'static [u8]>> {
+    /// Builds a new decoder over a sound embedded in the binary, for example with
+    /// `include_bytes!`.
+    ///
+    /// Attempts to automatically detect the format of the data. Nothing is copied, so
+    /// building a new decoder every time the sound plays is cheap, and the decoder can seek
+    /// since the data is in memory.
+    pub fn from_bytes(data: &'static [u8]) -> Result<Self, DecoderError> {
+        Self::new(Cursor::new(data))
+    }
+}
+
+impl<R> Decoder<R>
+where
+    R: Read + Seek,
+{
+    /// Sets a closure to call whenever a frame that could not be decoded is skipped.
+    ///
+    /// Corrupt frames do not end playback, the decoder moves on to the next frame it can
+    /// decode. The closure receives the number of frames skipped so far. When the data is
+    /// damaged beyond recovery, for example because the file is truncated, the decoder
+    /// ends the source instead.
+    ///
+    /// The WAV decoder can not tell corrupt samples apart and never calls the closure.
+    pub fn set_error_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(usize) + Send + 'static,
+    {
+        self.0.set_error_callback(Box::new(callback));
+    }
+
+    /// Returns the short name of the codec the data is encoded with, for example `"mp3"`,
+    /// `"vorbis"` or `"flac"`.
+    ///
+    /// The names for formats decoded through symphonia are the ones symphonia uses, these
+    /// can be more specific such as `"pcm_s16le"`.
+    pub fn codec(&self) -> Option<&'static str> {
+        self.0.codec()
+    }
+
+    /// Returns the number of bits per sample the file stores, before conversion to `i16`.
+    ///
+    /// Lossy formats such as MP3 and Vorbis have no fixed bit depth and return `None`.
+    pub fn bits_per_sample(&self) -> Option<u32> {
+        self.0.bits_per_sample()
+    }
+
+    /// Returns the tags, such as the title and artist, found while opening the file.
+    ///
+    /// This is best effort. Formats or decoders that can not read tags return an empty
+    /// `Metadata`.
+    pub fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    /// Returns the start and end of the loop region embedded in the file, if there is one.
+    ///
+    /// Only the `smpl` chunk of WAV files is read. The end is exclusive. Returns `None` for
+    /// other formats, or when the file does not define a loop.
+    pub fn loop_points(&self) -> Option<(Duration, Duration)> {
+        match &self.0 {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.loop_points(),
+            _ => None,
+        }
+    }
+}
+
 #[allow(missing_docs)] // Reason: will be removed, see: #612
 #[derive(Debug)]
 pub enum Mp4Type {
@@ -431,10 +783,30 @@ where
         self.0.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.0.total_len()
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        self.0.fill_buffer(out)
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.0.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.0.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.0.is_seekable()
+    }
 }
 
 impl<R> Iterator for LoopedDecoder<R>
@@ -487,9 +859,11 @@ where
                 }
                 #[cfg(feature = "symphonia")]
                 DecoderImpl::Symphonia(source) => {
+                    let gapless = source.gapless();
                     let mut reader = source.into_inner();
                     reader.seek(SeekFrom::Start(0)).ok()?;
-                    let mut source = symphonia::SymphoniaDecoder::new(reader, None).ok()?;
+                    let mut source =
+                        symphonia::SymphoniaDecoder::new(reader, None, gapless).ok()?;
                     let sample = source.next();
                     (DecoderImpl::Symphonia(source), sample)
                 }
@@ -533,6 +907,16 @@ where
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.0.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.0.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.0.is_seekable()
+    }
 }
 
 /// Error that can happen when creating a decoder.
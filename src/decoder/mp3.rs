@@ -4,6 +4,8 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::DecodeErrors;
+
 use minimp3::Decoder;
 use minimp3::Frame;
 use minimp3_fixed as minimp3;
@@ -16,6 +18,7 @@ where
     decoder: Decoder<R>,
     current_frame: Frame,
     current_frame_offset: usize,
+    errors: DecodeErrors,
 }
 
 impl<R> Mp3Decoder<R>
@@ -41,11 +44,34 @@ where
             decoder,
             current_frame,
             current_frame_offset: 0,
+            errors: DecodeErrors::default(),
         })
     }
     pub fn into_inner(self) -> R {
         self.decoder.into_inner()
     }
+
+    pub(crate) fn set_error_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        self.errors.set_callback(callback);
+    }
+
+    /// Decodes the next frame if the current one has been played. Returns `None` once there
+    /// is nothing left to decode.
+    fn refill(&mut self) -> Option<()> {
+        while self.current_frame_offset >= self.current_frame.data.len() {
+            match self.decoder.next_frame() {
+                // match self.decoder.decode_frame() {
+                Ok(frame) => {
+                    self.current_frame = frame;
+                    self.current_frame_offset = 0;
+                }
+                // minimp3 skipped over data it could not decode, the next frame may be fine
+                Err(minimp3::Error::SkippedData) => self.errors.report(),
+                Err(_) => return None,
+            }
+        }
+        Some(())
+    }
 }
 
 impl<R> Source for Mp3Decoder<R>
@@ -72,6 +98,19 @@ where
         None
     }
 
+    /// Copies whole frames at once instead of one sample at a time.
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        let mut filled = 0;
+        while filled < out.len() && self.refill().is_some() {
+            let available = &self.current_frame.data[self.current_frame_offset..];
+            let len = available.len().min(out.len() - filled);
+            out[filled..filled + len].copy_from_slice(&available[..len]);
+            self.current_frame_offset += len;
+            filled += len;
+        }
+        filled
+    }
+
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         // TODO waiting for PR in minimp3_fixed or minimp3
 
@@ -94,16 +133,7 @@ where
     type Item = i16;
 
     fn next(&mut self) -> Option<i16> {
-        if self.current_frame_offset == self.current_frame_len().unwrap() {
-            if let Ok(frame) = self.decoder.next_frame() {
-                // if let Ok(frame) = self.decoder.decode_frame() {
-                self.current_frame = frame;
-                self.current_frame_offset = 0;
-            } else {
-                return None;
-            }
-        }
-
+        self.refill()?;
         let v = self.current_frame.data[self.current_frame_offset];
         self.current_frame_offset += 1;
 
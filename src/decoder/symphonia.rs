@@ -6,16 +6,16 @@ use symphonia::{
         errors::Error,
         formats::{FormatOptions, FormatReader, SeekedTo},
         io::MediaSourceStream,
-        meta::MetadataOptions,
+        meta::{MetadataOptions, MetadataRevision, StandardTagKey},
         probe::Hint,
-        units::{self, Time},
+        units::{self, Time, TimeBase},
     },
     default::get_probe,
 };
 
 use crate::{source, Source};
 
-use super::DecoderError;
+use super::{DecodeErrors, DecoderError, Metadata};
 
 // Decoder errors are not considered fatal.
 // The correct action is to just get a new packet and try again.
@@ -27,16 +27,24 @@ pub(crate) struct SymphoniaDecoder {
     current_frame_offset: usize,
     format: Box<dyn FormatReader>,
     total_duration: Option<Time>,
+    total_frames: Option<u64>,
+    time_base: Option<TimeBase>,
+    // Samples played since the start, to count down the remaining length.
+    samples_played: u64,
     buffer: SampleBuffer<i16>,
     spec: SignalSpec,
+    gapless: bool,
+    errors: DecodeErrors,
+    metadata: Metadata,
 }
 
 impl SymphoniaDecoder {
     pub(crate) fn new(
         mss: MediaSourceStream,
         extension: Option<&str>,
+        gapless: bool,
     ) -> Result<Self, DecoderError> {
-        match SymphoniaDecoder::init(mss, extension) {
+        match SymphoniaDecoder::init(mss, extension, gapless) {
             Err(e) => match e {
                 Error::IoError(e) => Err(DecoderError::IoError(e.to_string())),
                 Error::DecodeError(e) => Err(DecoderError::DecodeError(e)),
@@ -56,21 +64,55 @@ impl SymphoniaDecoder {
         self.format.into_inner()
     }
 
+    pub(crate) fn set_error_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        self.errors.set_callback(callback);
+    }
+
+    pub(crate) fn codec(&self) -> Option<&'static str> {
+        let codec = self.decoder.codec_params().codec;
+        symphonia::default::get_codecs()
+            .get_codec(codec)
+            .map(|descriptor| descriptor.short_name)
+    }
+
+    pub(crate) fn bits_per_sample(&self) -> Option<u32> {
+        self.decoder.codec_params().bits_per_sample
+    }
+
+    pub(crate) fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Whether encoder delay and padding are trimmed from the decoded stream.
+    pub(crate) fn gapless(&self) -> bool {
+        self.gapless
+    }
+
     fn init(
         mss: MediaSourceStream,
         extension: Option<&str>,
+        gapless: bool,
     ) -> symphonia::core::errors::Result<Option<SymphoniaDecoder>> {
         let mut hint = Hint::new();
         if let Some(ext) = extension {
             hint.with_extension(ext);
         }
         let format_opts: FormatOptions = FormatOptions {
-            enable_gapless: true,
+            enable_gapless: gapless,
             ..Default::default()
         };
         let metadata_opts: MetadataOptions = Default::default();
         let mut probed = get_probe().format(&hint, mss, &format_opts, &metadata_opts)?;
 
+        // Tags can be in front of the container (ID3) or inside it, stored with the track.
+        let mut metadata = Metadata::default();
+        if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+            add_tags(&mut metadata, revision);
+        }
+        if let Some(revision) = probed.format.metadata().current() {
+            add_tags(&mut metadata, revision);
+        }
+
         let stream = match probed.format.default_track() {
             Some(stream) => stream,
             None => return Ok(None),
@@ -101,6 +143,8 @@ impl SymphoniaDecoder {
             .time_base
             .zip(stream.codec_params.n_frames)
             .map(|(base, frames)| base.calc_time(frames));
+        let total_frames = stream.codec_params.n_frames;
+        let time_base = stream.codec_params.time_base;
 
         let mut decode_errors: usize = 0;
         let decoded = loop {
@@ -116,6 +160,8 @@ impl SymphoniaDecoder {
             }
 
             match decoder.decode(&current_frame) {
+                // with gapless enabled a packet can be trimmed away entirely
+                Ok(decoded) if decoded.frames() == 0 => continue,
                 Ok(decoded) => break decoded,
                 Err(e) => match e {
                     Error::DecodeError(_) => {
@@ -137,8 +183,14 @@ impl SymphoniaDecoder {
             current_frame_offset: 0,
             format: probed.format,
             total_duration,
+            total_frames,
+            time_base,
+            samples_played: 0,
             buffer,
             spec,
+            gapless,
+            errors: DecodeErrors::default(),
+            metadata,
         }))
     }
 
@@ -173,7 +225,37 @@ impl Source for SymphoniaDecoder {
             .map(|Time { seconds, frac }| Duration::new(seconds, (1f64 / frac) as u32))
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        // with gapless playback the number of frames excludes the encoder delay and padding
+        self.total_frames
+            .map(|frames| frames * self.spec.channels.count() as u64)
+    }
+
+    /// Copies whole runs of decoded samples at once instead of one sample at a time.
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        let mut filled = 0;
+        while filled < out.len() && self.refill().is_some() {
+            let available = &self.buffer.samples()[self.current_frame_offset..];
+            let len = available.len().min(out.len() - filled);
+            out[filled..filled + len].copy_from_slice(&available[..len]);
+            self.current_frame_offset += len;
+            filled += len;
+        }
+        self.samples_played += filled as u64;
+        filled
+    }
+
     fn try_seek(&mut self, pos: Duration) -> Result<(), source::SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, source::SeekError> {
         use symphonia::core::formats::{SeekMode, SeekTo};
 
         let seek_beyond_end = self
@@ -201,10 +283,20 @@ impl Source for SymphoniaDecoder {
             )
             .map_err(SeekError::BaseSeek)?;
 
+        let required_ts = seek_res.required_ts;
         self.refine_position(seek_res)?;
         self.current_frame_offset += to_skip;
 
-        Ok(())
+        // the position is refined up to the requested timestamp
+        let landed = match self.time_base {
+            Some(TimeBase { numer, denom }) => Duration::from_nanos(
+                (required_ts as u128 * numer as u128 * 1_000_000_000 / denom as u128) as u64,
+            ),
+            None => pos,
+        };
+        let frames = (landed.as_nanos() * self.spec.rate as u128 + 500_000_000) / 1_000_000_000;
+        self.samples_played = frames as u64 * self.channels() as u64 + to_skip as u64;
+        Ok(landed)
     }
 }
 
@@ -254,6 +346,46 @@ impl SymphoniaDecoder {
     }
 }
 
+impl SymphoniaDecoder {
+    /// Decodes the next packet if the current one has been played. Returns `None` once
+    /// there is nothing left to decode.
+    fn refill(&mut self) -> Option<()> {
+        // with gapless enabled a packet can be trimmed away entirely, skip those
+        while self.current_frame_offset >= self.buffer.len() {
+            // Skip over packets that fail to decode, the format reader tells us when
+            // there is nothing left to read.
+            let decoded = loop {
+                let packet = self.format.next_packet().ok()?;
+                match self.decoder.decode(&packet) {
+                    Ok(decoded) => break decoded,
+                    Err(Error::DecodeError(_)) => self.errors.report(),
+                    Err(_) => return None,
+                }
+            };
+            decoded.spec().clone_into(&mut self.spec);
+            self.buffer = SymphoniaDecoder::get_buffer(decoded, &self.spec);
+            self.current_frame_offset = 0;
+        }
+        Some(())
+    }
+}
+
+fn add_tags(metadata: &mut Metadata, revision: &MetadataRevision) {
+    for tag in revision.tags() {
+        let value = tag.value.to_string();
+        let field = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => Some(&mut metadata.title),
+            Some(StandardTagKey::Artist) => Some(&mut metadata.artist),
+            Some(StandardTagKey::Album) => Some(&mut metadata.album),
+            _ => None,
+        };
+        if let Some(field) = field {
+            field.get_or_insert_with(|| value.clone());
+        }
+        metadata.tags.push((tag.key.clone(), value));
+    }
+}
+
 fn skip_back_a_tiny_bit(
     Time {
         mut seconds,
@@ -273,24 +405,23 @@ impl Iterator for SymphoniaDecoder {
 
     #[inline]
     fn next(&mut self) -> Option<i16> {
-        if self.current_frame_offset >= self.buffer.len() {
-            let packet = self.format.next_packet().ok()?;
-            let mut decoded = self.decoder.decode(&packet);
-            for _ in 0..MAX_DECODE_RETRIES {
-                if decoded.is_err() {
-                    let packet = self.format.next_packet().ok()?;
-                    decoded = self.decoder.decode(&packet);
-                }
-            }
-            let decoded = decoded.ok()?;
-            decoded.spec().clone_into(&mut self.spec);
-            self.buffer = SymphoniaDecoder::get_buffer(decoded, &self.spec);
-            self.current_frame_offset = 0;
-        }
-
+        self.refill()?;
         let sample = *self.buffer.samples().get(self.current_frame_offset)?;
         self.current_frame_offset += 1;
+        self.samples_played += 1;
 
         Some(sample)
     }
+
+    /// Exact if the format reports the number of frames, as WAV does.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.total_len() {
+            Some(total) => {
+                let remaining = total.saturating_sub(self.samples_played) as usize;
+                (remaining, Some(remaining))
+            }
+            None => (0, None),
+        }
+    }
 }
@@ -4,8 +4,14 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::{DecodeErrors, Metadata};
+
 use lewton::inside_ogg::OggStreamReader;
 
+// A packet that fails to decode is skipped, but more than this many failing
+// packets in a row means the stream can not be recovered.
+const MAX_DECODE_RETRIES: usize = 3;
+
 /// Decoder for an OGG file that contains Vorbis sound format.
 pub struct VorbisDecoder<R>
 where
@@ -14,6 +20,7 @@ where
     stream_reader: OggStreamReader<R>,
     current_data: Vec<i16>,
     next: usize,
+    errors: DecodeErrors,
 }
 
 impl<R> VorbisDecoder<R>
@@ -45,11 +52,49 @@ where
             stream_reader,
             current_data: data,
             next: 0,
+            errors: DecodeErrors::default(),
         }
     }
+    pub fn metadata(&self) -> Metadata {
+        let comments = &self.stream_reader.comment_hdr.comment_list;
+        Metadata::from_vorbis_comments(comments.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
     pub fn into_inner(self) -> OggStreamReader<R> {
         self.stream_reader
     }
+
+    pub(crate) fn set_error_callback(&mut self, callback: Box<dyn FnMut(usize) + Send>) {
+        self.errors.set_callback(callback);
+    }
+
+    /// Reads the next packet if the current one has been played. Returns `None` once there is
+    /// nothing left to decode.
+    fn refill(&mut self) -> Option<()> {
+        // packets can be empty, keep reading until there is data
+        while self.next >= self.current_data.len() {
+            self.current_data = self.read_packet()?;
+            self.next = 0;
+        }
+        Some(())
+    }
+
+    /// Reads the next packet, skipping packets that fail to decode.
+    fn read_packet(&mut self) -> Option<Vec<i16>> {
+        let mut decode_errors = 0;
+        loop {
+            match self.stream_reader.read_dec_packet_itl() {
+                Ok(data) => return data,
+                Err(_) => {
+                    self.errors.report();
+                    decode_errors += 1;
+                    if decode_errors > MAX_DECODE_RETRIES {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl<R> Source for VorbisDecoder<R>
@@ -76,6 +121,19 @@ where
         None
     }
 
+    /// Copies whole packets at once instead of one sample at a time.
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        let mut filled = 0;
+        while filled < out.len() && self.refill().is_some() {
+            let available = &self.current_data[self.next..];
+            let len = available.len().min(out.len() - filled);
+            out[filled..filled + len].copy_from_slice(&available[..len]);
+            self.next += len;
+            filled += len;
+        }
+        filled
+    }
+
     /// seek is broken, https://github.com/RustAudio/lewton/issues/73.
     // We could work around it by:
     //  - using unsafe to create an instance of Self
@@ -101,24 +159,10 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<i16> {
-        if let Some(sample) = self.current_data.get(self.next).copied() {
-            self.next += 1;
-            if self.current_data.is_empty() {
-                if let Ok(Some(data)) = self.stream_reader.read_dec_packet_itl() {
-                    self.current_data = data;
-                    self.next = 0;
-                }
-            }
-            Some(sample)
-        } else {
-            if let Ok(Some(data)) = self.stream_reader.read_dec_packet_itl() {
-                self.current_data = data;
-                self.next = 0;
-            }
-            let sample = self.current_data.get(self.next).copied();
-            self.next += 1;
-            sample
-        }
+        self.refill()?;
+        let sample = self.current_data[self.next];
+        self.next += 1;
+        Some(sample)
     }
 
     #[inline]
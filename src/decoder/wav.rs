@@ -4,7 +4,7 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
-use hound::{SampleFormat, WavReader};
+use hound::{SampleFormat, WavReader, WavSpec};
 
 /// Decoder for the WAV format.
 pub struct WavDecoder<R>
@@ -13,8 +13,10 @@ where
 {
     reader: SamplesIterator<R>,
     total_duration: Duration,
+    total_len: u64,
     sample_rate: u32,
     channels: u16,
+    loop_points: Option<(u32, u32)>,
 }
 
 impl<R> WavDecoder<R>
@@ -27,6 +29,7 @@ where
             return Err(data);
         }
 
+        let loop_points = read_loop_points(data.by_ref());
         let reader = WavReader::new(data).unwrap();
         let spec = reader.spec();
         let len = reader.len() as u64;
@@ -43,10 +46,28 @@ where
         Ok(WavDecoder {
             reader,
             total_duration,
+            total_len: len,
             sample_rate,
             channels,
+            loop_points,
         })
     }
+
+    /// Returns the start and end of the first loop stored in the `smpl` chunk, if any.
+    ///
+    /// The end is exclusive, so `end - start` is the length of the loop.
+    pub fn loop_points(&self) -> Option<(Duration, Duration)> {
+        let (start, end) = self.loop_points?;
+        let to_duration = |frame: u32| {
+            Duration::from_nanos(frame as u64 * 1_000_000_000 / self.sample_rate as u64)
+        };
+        Some((to_duration(start), to_duration(end)))
+    }
+
+    pub fn bits_per_sample(&self) -> u32 {
+        self.reader.reader.spec().bits_per_sample as u32
+    }
+
     pub fn into_inner(self) -> R {
         self.reader.reader.into_inner()
     }
@@ -60,6 +81,29 @@ where
     samples_read: u32, // wav header is u32 so this suffices
 }
 
+impl<R> SamplesIterator<R>
+where
+    R: Read + Seek,
+{
+    /// Reads samples until `out` is full or the file ends, looking at the format once
+    /// instead of for every sample.
+    fn fill(&mut self, out: &mut [i16]) -> usize {
+        let spec = self.reader.spec();
+        let filled = match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Float, 32) => read_into(self.reader.samples(), out, f32_to_i16),
+            (SampleFormat::Int, 8) => read_into(self.reader.samples(), out, i8_to_i16),
+            (SampleFormat::Int, 16) => read_into(self.reader.samples(), out, |value: i16| value),
+            (SampleFormat::Int, 24) => read_into(self.reader.samples(), out, i24_to_i16),
+            (SampleFormat::Int, 32) => read_into(self.reader.samples(), out, i32_to_i16),
+            (sample_format, bits_per_sample) => {
+                unreachable!("Unsupported wav spec: {sample_format:?}, {bits_per_sample}")
+            }
+        };
+        self.samples_read += filled as u32;
+        filled
+    }
+}
+
 impl<R> Iterator for SamplesIterator<R>
 where
     R: Read + Seek,
@@ -91,7 +135,7 @@ where
                 i32_to_i16(value.unwrap_or(0))
             }),
             (sample_format, bits_per_sample) => {
-                panic!("Unimplemented wav spec: {sample_format:?}, {bits_per_sample}")
+                unreachable!("Unsupported wav spec: {sample_format:?}, {bits_per_sample}")
             }
         }
     }
@@ -129,8 +173,28 @@ where
         Some(self.total_duration)
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        Some(self.total_len)
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [i16]) -> usize {
+        self.reader.fill(out)
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
         let file_len = self.reader.reader.duration();
 
         let new_pos = pos.as_secs_f32() * self.sample_rate() as f32;
@@ -150,7 +214,9 @@ where
             self.next();
         }
 
-        Ok(())
+        Ok(Duration::from_nanos(
+            new_pos as u64 * 1_000_000_000 / self.sample_rate as u64,
+        ))
     }
 }
 
@@ -173,20 +239,114 @@ where
 
 impl<R> ExactSizeIterator for WavDecoder<R> where R: Read + Seek {}
 
-/// Returns true if the stream contains WAV data, then resets it to where it was.
+/// Returns true if the stream contains WAV data we can decode, then resets it to where it was.
 fn is_wave<R>(mut data: R) -> bool
 where
     R: Read + Seek,
 {
     let stream_pos = data.stream_position().unwrap();
 
-    if WavReader::new(data.by_ref()).is_err() {
-        data.seek(SeekFrom::Start(stream_pos)).unwrap();
-        return false;
-    }
+    let supported = match WavReader::new(data.by_ref()) {
+        Ok(reader) => is_supported(reader.spec()),
+        Err(_) => false,
+    };
 
     data.seek(SeekFrom::Start(stream_pos)).unwrap();
-    true
+    supported
+}
+
+/// Looks for a `smpl` chunk and returns the first loop it defines as a `(start, end)` range
+/// of frames, with `end` exclusive. Resets the stream to where it was.
+///
+/// Any problem while walking the chunks means there are no loop points.
+fn read_loop_points<R>(mut data: R) -> Option<(u32, u32)>
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().ok()?;
+    let loop_points = find_smpl_loop(data.by_ref());
+    data.seek(SeekFrom::Start(stream_pos)).ok()?;
+    loop_points
+}
+
+fn find_smpl_loop<R>(mut data: R) -> Option<(u32, u32)>
+where
+    R: Read + Seek,
+{
+    // Offsets within the chunk, see the `smpl` chunk layout in the RIFF spec.
+    const NUM_SAMPLE_LOOPS: usize = 28;
+    const FIRST_LOOP: usize = 36;
+    const LOOP_LEN: usize = 24;
+
+    let mut header = [0u8; 12];
+    data.read_exact(&mut header).ok()?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return None;
+    }
+
+    loop {
+        let mut chunk_header = [0u8; 8];
+        data.read_exact(&mut chunk_header).ok()?;
+        let chunk_len = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap()) as usize;
+
+        if &chunk_header[0..4] != b"smpl" {
+            // chunks are padded to an even number of bytes
+            let skip = chunk_len + chunk_len % 2;
+            data.seek(SeekFrom::Current(skip as i64)).ok()?;
+            continue;
+        }
+
+        if chunk_len < FIRST_LOOP + LOOP_LEN {
+            return None;
+        }
+        let mut chunk = vec![0u8; FIRST_LOOP + LOOP_LEN];
+        data.read_exact(&mut chunk).ok()?;
+        let read_u32 =
+            |offset: usize| u32::from_le_bytes(chunk[offset..offset + 4].try_into().unwrap());
+
+        if read_u32(NUM_SAMPLE_LOOPS) == 0 {
+            return None;
+        }
+        let start = read_u32(FIRST_LOOP + 8);
+        let end = read_u32(FIRST_LOOP + 12);
+        // the end stored in the chunk is the last frame that is part of the loop
+        return (start <= end).then(|| (start, end.saturating_add(1)));
+    }
+}
+
+/// Returns true if the samples of a stream with this spec can be converted to i16.
+///
+/// Hound accepts headers it cannot read samples for, for example a 20 bit
+/// `WAVE_FORMAT_EXTENSIBLE` stream. Those would decode as silence, so they are
+/// rejected up front instead.
+fn is_supported(spec: WavSpec) -> bool {
+    matches!(
+        (spec.sample_format, spec.bits_per_sample),
+        (SampleFormat::Float, 32)
+            | (SampleFormat::Int, 8)
+            | (SampleFormat::Int, 16)
+            | (SampleFormat::Int, 24)
+            | (SampleFormat::Int, 32)
+    )
+}
+
+/// Converts samples into `out` until either runs out, reading unreadable ones as silence like
+/// `SamplesIterator::next` does.
+fn read_into<T, E>(
+    samples: impl Iterator<Item = Result<T, E>>,
+    out: &mut [i16],
+    convert: impl Fn(T) -> i16,
+) -> usize
+where
+    T: Default,
+{
+    let mut filled = 0;
+    // `out` comes first so no sample is read once it is full
+    for (slot, value) in out.iter_mut().zip(samples) {
+        *slot = convert(value.unwrap_or_default());
+        filled += 1;
+    }
+    filled
 }
 
 /// Returns a 32 bit WAV float as an i16. WAV floats are typically in the range of
@@ -1,10 +1,13 @@
 //! Mixer that plays multiple sounds at the same time.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use crate::source::{SeekError, Source, UniformSourceIterator};
+use cpal::Sample as CpalSample;
+use crossbeam::atomic::AtomicCell;
+
+use crate::source::{FormatError, SeekError, SmoothedParam, Source, UniformSourceIterator};
 use crate::Sample;
 
 /// Builds a new mixer.
@@ -12,7 +15,24 @@ use crate::Sample;
 /// You can choose the characteristics of the output thanks to this constructor. All the sounds
 /// added to the mixer will be converted to these values.
 ///
-/// After creating a mixer, you can add new sounds with the controller.
+/// Sounds with a different sample rate are resampled with linear interpolation, keeping
+/// their duration. That includes sounds with a higher rate than the mixer: every input frame
+/// is read and the output is interpolated between them, nothing is cut off. Sounds with a
+/// different number of channels are up or down mixed.
+///
+/// After creating a mixer, you can add new sounds with the controller. The mixer does not need
+/// an output device, so it can also be used to render audio offline by reading from the
+/// returned [`DynamicMixer`], see [`Sink::connect_new`](crate::Sink::connect_new).
+///
+/// Mixing is deterministic. Sources are summed in the order they were added and nothing in
+/// the mixer or the conversions it applies is random, so rendering the same sources twice
+/// produces bit identical output. That only holds if the sources are added at the same
+/// sample positions each time: a source added from another thread while the mixer is being
+/// read starts at whatever sample the mixer happens to be at.
+///
+/// # Panics
+///
+/// Panics if `channels` or `sample_rate` is zero.
 pub fn mixer<S>(
     channels: u16,
     sample_rate: u32,
@@ -20,28 +40,307 @@ pub fn mixer<S>(
 where
     S: Sample + Send + 'static,
 {
+    assert!(channels != 0, "a mixer needs at least one channel");
+    assert!(sample_rate != 0, "a mixer needs a sample rate above zero");
     let input = Arc::new(DynamicMixerController {
         has_pending: AtomicBool::new(false),
         pending_sources: Mutex::new(Vec::new()),
+        active_sources: AtomicUsize::new(0),
+        recent_peak: AtomicCell::new(0.0),
+        headroom: AtomicU64::new(Headroom::None.to_bits()),
+        max_voices: AtomicUsize::new(NO_VOICE_LIMIT),
+        labels: Mutex::new(Vec::new()),
         channels,
         sample_rate,
     });
 
+    let peak_window = PEAK_WINDOW.as_millis() as usize * sample_rate as usize / 1000;
     let output = DynamicMixer {
         current_sources: Vec::with_capacity(16),
         input: input.clone(),
         sample_count: 0,
         still_pending: vec![],
         still_current: vec![],
+        peak: 0.0,
+        peak_samples: 0,
+        peak_window: (peak_window * channels as usize).max(1),
+        headroom_gain: SmoothedParam::linear(1.0, HEADROOM_GLIDE, sample_rate * channels as u32),
+        applied_headroom: (Headroom::None, 0),
+        max_voices: None,
+        level_release: (-1.0
+            / (LEVEL_RELEASE.as_secs_f32() * sample_rate as f32 * channels as f32))
+            .exp(),
     };
 
     (input, output)
 }
 
+/// The peak of the output is measured over windows of this length.
+const PEAK_WINDOW: Duration = Duration::from_millis(50);
+
+/// Output below this level, about -80 dBFS, is considered silent.
+const SILENCE_THRESHOLD: f32 = 1e-4;
+
+/// Changes of the headroom gain are spread over this duration to avoid clicks.
+const HEADROOM_GLIDE: Duration = Duration::from_millis(10);
+
+/// Stored as the maximum number of voices when there is no limit.
+const NO_VOICE_LIMIT: usize = usize::MAX;
+
+/// Sources dropped because of the voice limit fade out over this duration.
+const VOICE_FADE: Duration = Duration::from_millis(10);
+
+/// The level of a source used to find the quietest one falls back by about 63% over this
+/// duration once the source gets quieter.
+const LEVEL_RELEASE: Duration = Duration::from_millis(100);
+
+/// How much the mixer turns down the sum of its sources, to keep it from clipping.
+///
+/// Sources that are each at full volume clip when they are summed. Headroom trades loudness
+/// for distortion free output: the mix gets quieter, but loud moments no longer clip. It is
+/// a static alternative to a limiter or compressor, the gain does not depend on the signal.
+///
+/// The mixer sums before it applies the headroom, so it only helps with floating point
+/// samples. Integer samples, like the `i16` of a mixer built for them, saturate while being
+/// summed. The mixer of an [`OutputStream`](crate::OutputStream) uses floats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Headroom {
+    /// The sum is played as is. This is the default.
+    None,
+    /// Turns the sum down by a fixed number of decibels, `12.0` plays it at -12 dB.
+    Decibels(f32),
+    /// Divides the sum by the number of sources playing, so it never clips as long as every
+    /// source stays within full scale. Adding a source makes all others quieter, the volume
+    /// glides to the new level over a few milliseconds.
+    PerSource,
+}
+
+impl Headroom {
+    fn gain(self, sources: usize) -> f32 {
+        match self {
+            Headroom::None => 1.0,
+            Headroom::Decibels(db) => 10f32.powf(-db.abs() / 20.0),
+            Headroom::PerSource => 1.0 / sources.max(1) as f32,
+        }
+    }
+
+    /// Packs the headroom in a `u64`, to store it in an atomic the audio thread can read
+    /// without a lock. The variant is in the upper half, the decibels in the lower.
+    fn to_bits(self) -> u64 {
+        match self {
+            Headroom::None => 0,
+            Headroom::Decibels(db) => 1 << 32 | db.to_bits() as u64,
+            Headroom::PerSource => 2 << 32,
+        }
+    }
+
+    /// Unpacks a headroom packed with `to_bits`.
+    fn from_bits(bits: u64) -> Headroom {
+        match bits >> 32 {
+            1 => Headroom::Decibels(f32::from_bits(bits as u32)),
+            2 => Headroom::PerSource,
+            _ => Headroom::None,
+        }
+    }
+}
+
+/// Stops a source added with
+/// [`add_with_handle`](DynamicMixerController::add_with_handle).
+///
+/// This is the least a source can be controlled with. The mixer checks the handle before it
+/// reads each sample of the source, nothing else is added between the source and the mixer.
+/// Compared to a [`Sink`](crate::Sink) there is no queue, no pause, no volume, no speed and no
+/// seeking, and nothing runs periodically next to the source. The source starts at the next
+/// frame the mixer produces, stopping it cuts it off at once.
+///
+/// Dropping the handle leaves the source playing.
+#[derive(Clone, Debug)]
+pub struct SourceHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl SourceHandle {
+    /// Stops the source, the mixer drops it before its next sample.
+    #[inline]
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Controls a source added with
+/// [`add_oneshot`](DynamicMixerController::add_oneshot). Dropping it leaves the source
+/// playing.
+#[derive(Clone)]
+pub struct OneShotHandle {
+    controls: Arc<OneShotControls>,
+}
+
+struct OneShotControls {
+    volume: AtomicCell<f32>,
+    stopped: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl Default for OneShotControls {
+    fn default() -> Self {
+        OneShotControls {
+            volume: AtomicCell::new(1.0),
+            stopped: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        }
+    }
+}
+
+impl OneShotHandle {
+    /// Stops the source, the mixer drops it at the next sample.
+    #[inline]
+    pub fn stop(&self) {
+        self.controls.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the volume of the source, `1.0` is its original volume.
+    #[inline]
+    pub fn volume(&self) -> f32 {
+        self.controls.volume.load()
+    }
+
+    /// Changes the volume of the source, `1.0` is its original volume.
+    #[inline]
+    pub fn set_volume(&self, value: f32) {
+        self.controls.volume.store(value);
+    }
+
+    /// Returns true once the source ended, was stopped or was dropped by the mixer, for
+    /// example because of the voice limit or because the mixer itself was dropped.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.controls.finished.load(Ordering::Relaxed)
+    }
+}
+
+/// Applies the controls of a `OneShotHandle` to a source of the mixer.
+struct OneShot<T> {
+    input: T,
+    controls: Arc<OneShotControls>,
+}
+
+impl<T> Drop for OneShot<T> {
+    fn drop(&mut self) {
+        self.controls.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Iterator for OneShot<T>
+where
+    T: Source,
+    T::Item: Sample,
+{
+    type Item = T::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<T::Item> {
+        if self.controls.stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+        let value = self.input.next()?;
+        Some(value.amplify(self.controls.volume.load()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<T> Source for OneShot<T>
+where
+    T: Source,
+    T::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+/// A source that is mixed, with what the voice limit needs to know about it.
+struct Voice<S> {
+    source: Box<dyn Source<Item = S> + Send>,
+    priority: i32,
+    // Recent absolute level, starts at full scale until the source has been heard.
+    level: f32,
+    // Set once the source is dropped because of the voice limit.
+    fade_out: Option<SmoothedParam>,
+    // Set when the source ends, its entry in the labels of the controller is then removed
+    // outside of the audio thread. `None` if it has no label.
+    label_ended: Option<Arc<AtomicBool>>,
+    // Set by the `SourceHandle` of the source, if it has one.
+    stop: Option<Arc<AtomicBool>>,
+}
+
+/// A label that can change while the source it belongs to plays, like that of a `Sink`.
+pub(crate) type SharedLabel = Arc<Mutex<Option<String>>>;
+
 /// The input of the mixer.
 pub struct DynamicMixerController<S> {
     has_pending: AtomicBool,
-    pending_sources: Mutex<Vec<Box<dyn Source<Item = S> + Send>>>,
+    pending_sources: Mutex<Vec<Voice<S>>>,
+    // Sources that were added and have not ended yet, pending ones included.
+    active_sources: AtomicUsize,
+    // Highest absolute output sample of the last complete peak window.
+    recent_peak: AtomicCell<f32>,
+    // See `Headroom::to_bits`.
+    headroom: AtomicU64,
+    // `NO_VOICE_LIMIT` if there is no limit.
+    max_voices: AtomicUsize,
+    // The labels of the sources that have one, in the order they were added, with the flag
+    // that tells whether the source ended.
+    labels: Mutex<Vec<(Arc<AtomicBool>, SharedLabel)>>,
     channels: u16,
     sample_rate: u32,
 }
@@ -50,25 +349,287 @@ impl<S> DynamicMixerController<S>
 where
     S: Sample + Send + 'static,
 {
+    /// Returns the number of channels sources are converted to.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Returns the sample rate sources are converted to.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the number of sources that are mixed, counting from the moment they are
+    /// added until they end.
+    ///
+    /// A [`Sink`](crate::Sink) counts as a single source for as long as it exists, even
+    /// while it is empty or paused, see [`is_silent`](DynamicMixerController::is_silent) to
+    /// find out whether anything can be heard.
+    #[inline]
+    pub fn active_sources(&self) -> usize {
+        self.active_sources.load(Ordering::Relaxed)
+    }
+
+    /// Returns the labels of the active sources that have one, in the order the sources were
+    /// added. Sources get a label with [`Source::labeled`], a [`Sink`](crate::Sink) with
+    /// [`Sink::set_label`](crate::Sink::set_label).
+    ///
+    /// The label of a source is read when it is added, sources without a label at that
+    /// moment are not listed. Unlabeled sources cost nothing extra.
+    pub fn active_labels(&self) -> Vec<String> {
+        let mut labels = self.labels.lock().unwrap();
+        retain_active(&mut labels);
+        labels
+            .iter()
+            .filter_map(|(_, label)| label.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// Returns the highest absolute sample value, between `0.0` and full scale at `1.0`, the
+    /// mixer produced during the most recent 50 milliseconds it was played.
+    #[inline]
+    pub fn recent_peak(&self) -> f32 {
+        self.recent_peak.load()
+    }
+
+    /// Returns true if nothing audible came out of the mixer recently, either because no
+    /// sources are left or because they are all silent.
+    ///
+    /// Useful to show an "audio active" indicator or to decide whether the output stream
+    /// can be paused to save power.
+    #[inline]
+    pub fn is_silent(&self) -> bool {
+        self.active_sources() == 0 || self.recent_peak() < SILENCE_THRESHOLD
+    }
+
+    /// Returns the headroom applied to the mix.
+    #[inline]
+    pub fn headroom(&self) -> Headroom {
+        Headroom::from_bits(self.headroom.load(Ordering::Relaxed))
+    }
+
+    /// Sets how much to turn down the sum of the sources, see [`Headroom`]. A change glides
+    /// to the new volume over a few milliseconds.
+    #[inline]
+    pub fn set_headroom(&self, headroom: Headroom) {
+        self.headroom.store(headroom.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the maximum number of sources mixed at the same time, `None` if there is no
+    /// limit.
+    #[inline]
+    pub fn max_voices(&self) -> Option<usize> {
+        match self.max_voices.load(Ordering::Relaxed) {
+            NO_VOICE_LIMIT => None,
+            max_voices => Some(max_voices),
+        }
+    }
+
+    /// Limits the number of sources mixed at the same time, so that the mixer degrades
+    /// gracefully instead of glitching when too many sounds play. `None`, the default, mixes
+    /// every source.
+    ///
+    /// Whenever more sources play than allowed, the one with the lowest priority is dropped,
+    /// see [`add_with_priority`](DynamicMixerController::add_with_priority). Between sources
+    /// of the same priority the quietest goes first. The level of a source is measured while
+    /// there is a limit, until then, and right after it starts, it counts as loud. Dropped
+    /// sources fade out over a few milliseconds and do not come back. Sources
+    /// that end free their slot for the next one that is added.
+    #[inline]
+    pub fn set_max_voices(&self, max_voices: Option<usize>) {
+        self.max_voices
+            .store(max_voices.unwrap_or(NO_VOICE_LIMIT), Ordering::Relaxed);
+        // the mixer applies the limit when it starts pending sources
+        self.has_pending.store(true, Ordering::SeqCst);
+    }
+
     /// Adds a new source to mix to the existing ones.
+    ///
+    /// The source starts playing at the next frame the mixer produces. It is summed after
+    /// all sources added before it. It has priority `0`, see
+    /// [`add_with_priority`](DynamicMixerController::add_with_priority).
+    ///
+    /// Sources that report zero channels or an invalid sample rate can not be played and are
+    /// dropped, see [`try_add`](DynamicMixerController::try_add) to find out.
     #[inline]
     pub fn add<T>(&self, source: T)
     where
         T: Source<Item = S> + Send + 'static,
     {
+        let _rejected = self.try_add(source);
+    }
+
+    /// Adds a new source to mix to the existing ones, see
+    /// [`add`](DynamicMixerController::add).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and drops the source, if it reports zero channels, a sample rate of
+    /// zero or one above [`FormatError::MAX_SAMPLE_RATE`].
+    #[inline]
+    pub fn try_add<T>(&self, source: T) -> Result<(), FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        self.try_add_with_priority(source, 0)
+    }
+
+    /// Adds a new source with a priority for the voice limit, see
+    /// [`set_max_voices`](DynamicMixerController::set_max_voices). Sources with a higher
+    /// priority are kept over those with a lower one, the priority of [`add`] is `0`.
+    ///
+    /// [`add`]: DynamicMixerController::add
+    #[inline]
+    pub fn add_with_priority<T>(&self, source: T, priority: i32)
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let _rejected = self.try_add_with_priority(source, priority);
+    }
+
+    /// Adds a new source with a priority for the voice limit, see
+    /// [`add_with_priority`](DynamicMixerController::add_with_priority).
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`try_add`](DynamicMixerController::try_add).
+    pub fn try_add_with_priority<T>(&self, source: T, priority: i32) -> Result<(), FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let label = source
+            .label()
+            .map(|label| Arc::new(Mutex::new(Some(label.to_owned()))));
+        self.push(source, priority, label, None)
+    }
+
+    /// Adds a new source like [`add`](DynamicMixerController::add) and returns a handle that
+    /// can only stop it, see [`SourceHandle`].
+    #[inline]
+    pub fn add_with_handle<T>(&self, source: T) -> SourceHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let _rejected = self.push(source, 0, None, Some(stop.clone()));
+        SourceHandle { stop }
+    }
+
+    /// Adds a new source and returns a handle that can stop it, see
+    /// [`add_with_handle`](DynamicMixerController::add_with_handle).
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`try_add`](DynamicMixerController::try_add).
+    pub fn try_add_with_handle<T>(&self, source: T) -> Result<SourceHandle, FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        self.push(source, 0, None, Some(stop.clone()))?;
+        Ok(SourceHandle { stop })
+    }
+
+    /// Plays a source once, fire and forget, see
+    /// [`try_add_oneshot`](DynamicMixerController::try_add_oneshot).
+    #[inline]
+    pub fn add_oneshot<T>(&self, source: T) -> OneShotHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let controls = Arc::new(OneShotControls::default());
+        let _rejected = self.try_add(OneShot {
+            input: source,
+            controls: controls.clone(),
+        });
+        OneShotHandle { controls }
+    }
+
+    /// Plays a source once, fire and forget. It is mixed like any other source and dropped
+    /// as soon as it ends, there is nothing to keep alive or clean up.
+    ///
+    /// The returned handle can stop the source early or change its volume. Dropping the
+    /// handle does not stop the source, unlike dropping a [`Sink`](crate::Sink).
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`try_add`](DynamicMixerController::try_add).
+    pub fn try_add_oneshot<T>(&self, source: T) -> Result<OneShotHandle, FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let controls = Arc::new(OneShotControls::default());
+        self.try_add(OneShot {
+            input: source,
+            controls: controls.clone(),
+        })?;
+        Ok(OneShotHandle { controls })
+    }
+
+    /// Adds a new source whose label, shown in `active_labels`, can change while it plays.
+    pub(crate) fn try_add_labeled<T>(
+        &self,
+        source: T,
+        label: SharedLabel,
+    ) -> Result<(), FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        self.push(source, 0, Some(label), None)
+    }
+
+    fn push<T>(
+        &self,
+        source: T,
+        priority: i32,
+        label: Option<SharedLabel>,
+        stop: Option<Arc<AtomicBool>>,
+    ) -> Result<(), FormatError>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        FormatError::check_new(&source)?;
+        let label_ended = label.map(|label| {
+            let ended = Arc::new(AtomicBool::new(false));
+            let mut labels = self.labels.lock().unwrap();
+            retain_active(&mut labels);
+            labels.push((ended.clone(), label));
+            ended
+        });
         let uniform_source = UniformSourceIterator::new(source, self.channels, self.sample_rate);
-        self.pending_sources
-            .lock()
-            .unwrap()
-            .push(Box::new(uniform_source) as Box<_>);
+        self.pending_sources.lock().unwrap().push(Voice {
+            source: Box::new(uniform_source) as Box<_>,
+            priority,
+            level: 1.0,
+            fade_out: None,
+            label_ended,
+            stop,
+        });
+        self.active_sources.fetch_add(1, Ordering::Relaxed);
         self.has_pending.store(true, Ordering::SeqCst); // TODO: can we relax this ordering?
+        Ok(())
+    }
+
+    /// Forgets a source that ended. Called from the audio thread, so it does not lock.
+    fn end_source(&self, label_ended: Option<Arc<AtomicBool>>) {
+        self.active_sources.fetch_sub(1, Ordering::Relaxed);
+        if let Some(ended) = label_ended {
+            ended.store(true, Ordering::Relaxed);
+        }
     }
 }
 
+/// Removes the labels of the sources that ended.
+fn retain_active(labels: &mut Vec<(Arc<AtomicBool>, SharedLabel)>) {
+    labels.retain(|(ended, _)| !ended.load(Ordering::Relaxed));
+}
+
 /// The output of the mixer. Implements `Source`.
 pub struct DynamicMixer<S> {
     // The current iterator that produces samples.
-    current_sources: Vec<Box<dyn Source<Item = S> + Send>>,
+    current_sources: Vec<Voice<S>>,
 
     // The pending sounds.
     input: Arc<DynamicMixerController<S>>,
@@ -77,10 +638,26 @@ pub struct DynamicMixer<S> {
     sample_count: usize,
 
     // A temporary vec used in start_pending_sources.
-    still_pending: Vec<Box<dyn Source<Item = S> + Send>>,
+    still_pending: Vec<Voice<S>>,
 
     // A temporary vec used in sum_current_sources.
-    still_current: Vec<Box<dyn Source<Item = S> + Send>>,
+    still_current: Vec<Voice<S>>,
+
+    // Highest absolute output sample in the current peak window.
+    peak: f32,
+    // Samples in the current peak window so far, and in a complete window.
+    peak_samples: usize,
+    peak_window: usize,
+
+    // Gain applied to the sum, and the headroom and number of sources it was computed for.
+    headroom_gain: SmoothedParam,
+    applied_headroom: (Headroom, usize),
+
+    // The voice limit of the controller, read when pending sources are started.
+    max_voices: Option<usize>,
+
+    // Per sample decay of the level of the sources.
+    level_release: f32,
 }
 
 impl<S> Source for DynamicMixer<S>
@@ -159,6 +736,8 @@ where
         self.sample_count += 1;
 
         let sum = self.sum_current_sources();
+        let sum = self.apply_headroom(sum);
+        self.track_peak(sum);
 
         if self.current_sources.is_empty() {
             None
@@ -177,6 +756,24 @@ impl<S> DynamicMixer<S>
 where
     S: Sample + Send + 'static,
 {
+    /// Fills all of `out`, with silence where no source plays. Meant for output callbacks.
+    ///
+    /// Unlike [`fill_buffer`](Source::fill_buffer) this does not stop at a moment where no
+    /// source plays, so a source added while the buffer is filled starts right away. Every
+    /// sample written counts towards the position of the mixer, which keeps the channels of
+    /// sources that start later in the right place.
+    pub fn fill_output(&mut self, out: &mut [S]) {
+        let mut filled = 0;
+        while filled < out.len() {
+            filled += self.fill_buffer(&mut out[filled..]);
+            if filled < out.len() {
+                // nothing played for this sample
+                out[filled] = S::zero_value();
+                filled += 1;
+            }
+        }
+    }
+
     // Samples from the #next() function are interlaced for each of the channels.
     // We need to ensure we start playing sources so that their samples are
     // in-step with the modulo of the samples produced so far. Otherwise, the
@@ -184,34 +781,116 @@ where
     fn start_pending_sources(&mut self) {
         let mut pending = self.input.pending_sources.lock().unwrap(); // TODO: relax ordering?
 
-        for source in pending.drain(..) {
-            let in_step = self.sample_count % source.channels() as usize == 0;
+        for voice in pending.drain(..) {
+            let in_step = self.sample_count % voice.source.channels() as usize == 0;
 
             if in_step {
-                self.current_sources.push(source);
+                self.current_sources.push(voice);
             } else {
-                self.still_pending.push(source);
+                self.still_pending.push(voice);
             }
         }
         std::mem::swap(&mut self.still_pending, &mut pending);
 
         let has_pending = !pending.is_empty();
         self.input.has_pending.store(has_pending, Ordering::SeqCst); // TODO: relax ordering?
+        drop(pending);
+
+        self.max_voices = self.input.max_voices();
+        if let Some(max_voices) = self.max_voices {
+            self.limit_voices(max_voices);
+        }
+    }
+
+    /// Fades out the sources of the lowest priority, the quietest first, until no more than
+    /// `max_voices` are left that are not fading out.
+    fn limit_voices(&mut self, max_voices: usize) {
+        let playing = self
+            .current_sources
+            .iter()
+            .filter(|voice| voice.fade_out.is_none())
+            .count();
+        let update_rate = self.input.sample_rate * self.input.channels as u32;
+        for _ in max_voices..playing {
+            let Some(voice) = self
+                .current_sources
+                .iter_mut()
+                .filter(|voice| voice.fade_out.is_none())
+                .min_by(|a, b| {
+                    a.priority
+                        .cmp(&b.priority)
+                        .then(a.level.total_cmp(&b.level))
+                })
+            else {
+                break;
+            };
+            let mut fade_out = SmoothedParam::linear(1.0, VOICE_FADE, update_rate);
+            fade_out.set_target(0.0);
+            voice.fade_out = Some(fade_out);
+        }
     }
 
     fn sum_current_sources(&mut self) -> S {
         let mut sum = S::zero_value();
+        let track_levels = self.max_voices.is_some();
 
-        for mut source in self.current_sources.drain(..) {
-            if let Some(value) = source.next() {
-                sum = sum.saturating_add(value);
-                self.still_current.push(source);
+        for mut voice in self.current_sources.drain(..) {
+            if voice
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed))
+            {
+                self.input.end_source(voice.label_ended);
+                continue;
+            }
+            let Some(mut value) = voice.source.next() else {
+                self.input.end_source(voice.label_ended);
+                continue;
+            };
+            if let Some(fade_out) = voice.fade_out.as_mut() {
+                if !fade_out.is_smoothing() {
+                    // faded out, dropped for the voice limit
+                    self.input.end_source(voice.label_ended);
+                    continue;
+                }
+                value = value.amplify(fade_out.next_value());
+            } else if track_levels {
+                let level = value.to_float_sample().to_sample::<f32>().abs();
+                voice.level = level.max(voice.level * self.level_release);
             }
+            sum = sum.saturating_add(value);
+            self.still_current.push(voice);
         }
         std::mem::swap(&mut self.still_current, &mut self.current_sources);
 
         sum
     }
+
+    fn apply_headroom(&mut self, sum: S) -> S {
+        let headroom = Headroom::from_bits(self.input.headroom.load(Ordering::Relaxed));
+        let applied = (headroom, self.current_sources.len());
+        if applied != self.applied_headroom {
+            self.applied_headroom = applied;
+            self.headroom_gain.set_target(applied.0.gain(applied.1));
+        }
+        let gain = self.headroom_gain.next_value();
+        if gain == 1.0 {
+            sum
+        } else {
+            sum.amplify(gain)
+        }
+    }
+
+    fn track_peak(&mut self, sample: S) {
+        let level = sample.to_float_sample().to_sample::<f32>().abs();
+        self.peak = self.peak.max(level);
+        self.peak_samples += 1;
+        if self.peak_samples >= self.peak_window {
+            self.input.recent_peak.store(self.peak);
+            self.peak = 0.0;
+            self.peak_samples = 0;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -300,4 +979,288 @@ mod tests {
 
         assert_eq!(rx.next(), None);
     }
+
+    #[test]
+    fn short_sound_is_played_completely() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        // 3ms, ending partway through the second buffer
+        let sound: Vec<f32> = (1..=288).map(|n| n as f32).collect();
+        tx.add(SamplesBuffer::new(2, 48000, sound.clone()));
+
+        let mut out = Vec::new();
+        let mut buffer = [f32::NAN; 256];
+        for _ in 0..2 {
+            rx.fill_output(&mut buffer);
+            out.extend_from_slice(&buffer);
+        }
+        assert_eq!(out[..288], sound[..]);
+        assert!(out[288..].iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn sound_after_silence_starts_on_first_channel() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let mut buffer = [f32::NAN; 6];
+        rx.fill_output(&mut buffer);
+        assert_eq!(buffer, [0.0; 6]);
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![1.0f32, 2.0]));
+        rx.fill_output(&mut buffer);
+        assert_eq!(buffer, [1.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn sums_in_the_order_sources_were_added() {
+        // float addition is not associative, the result depends on the order of the sources
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.add(SamplesBuffer::new(1, 48000, vec![1e8f32]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1.0f32]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![-1e8f32]));
+        assert_eq!(rx.next(), Some(0.0));
+
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.add(SamplesBuffer::new(1, 48000, vec![1e8f32]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![-1e8f32]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1.0f32]));
+        assert_eq!(rx.next(), Some(1.0));
+    }
+
+    #[test]
+    fn renders_are_reproducible() {
+        use crate::source::SineWave;
+        use std::time::Duration;
+
+        let render = || {
+            let (tx, rx) = dynamic_mixer::mixer(2, 44100);
+            for (i, freq) in [220.0, 330.0, 441.5, 1000.0].into_iter().enumerate() {
+                let source = SineWave::new(freq)
+                    .take_duration(Duration::from_millis(20 + 5 * i as u64))
+                    .amplify(0.3)
+                    .speed(1.0 + i as f32 * 0.1);
+                tx.add(source);
+            }
+            rx.map(f32::to_bits).collect::<Vec<_>>()
+        };
+        let first = render();
+        assert!(!first.is_empty());
+        assert_eq!(first, render());
+    }
+
+    #[test]
+    fn rejects_sources_without_channels() {
+        use crate::source::{FormatError, Zero};
+
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        assert_eq!(
+            tx.try_add(Zero::new_samples(0, 48000, 10)),
+            Err(FormatError::ZeroChannels)
+        );
+        assert_eq!(
+            tx.try_add(Zero::new_samples(1, 0, 10)),
+            Err(FormatError::ZeroSampleRate)
+        );
+        tx.add(Zero::new_samples(0, 0, 10));
+        assert_eq!(tx.active_sources(), 0);
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn rejects_extreme_sample_rates() {
+        use crate::source::FormatError;
+
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let rate = FormatError::MAX_SAMPLE_RATE + 1;
+        assert_eq!(
+            tx.try_add(SamplesBuffer::new(1, rate, vec![1.0f32; 10])),
+            Err(FormatError::SampleRateTooHigh(rate))
+        );
+
+        // very low rates are fine, the conversion works one frame at a time
+        tx.add(SamplesBuffer::new(1, 1, vec![1.0f32, 1.0]));
+        assert_eq!(rx.by_ref().take(48000).filter(|&s| s == 1.0).count(), 48000);
+    }
+
+    #[test]
+    fn voice_limit_drops_lowest_priority() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 1000);
+        tx.set_max_voices(Some(2));
+        tx.add_with_priority(SamplesBuffer::new(1, 1000, vec![0.1f32; 1000]), 1);
+        tx.add_with_priority(SamplesBuffer::new(1, 1000, vec![0.2f32; 1000]), 0);
+        tx.add_with_priority(SamplesBuffer::new(1, 1000, vec![0.4f32; 1000]), 2);
+        assert_eq!(tx.active_sources(), 3);
+
+        // the lowest priority fades out over 10ms
+        let fade: Vec<f32> = rx.by_ref().take(20).collect();
+        assert!(fade.windows(2).all(|w| w[1] <= w[0]));
+        assert!((fade[19] - 0.5).abs() < 1e-6);
+        assert_eq!(tx.active_sources(), 2);
+
+        // an ended source frees its slot
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.2f32; 10]));
+        rx.by_ref().take(40).for_each(drop);
+        assert_eq!(tx.active_sources(), 2);
+        tx.set_max_voices(None);
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.2f32; 10]));
+        assert!((rx.next().unwrap() - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stereo_source_on_mono_mixer() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        // only the left channel, halved
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.5f32, 0.0, 0.5, 0.0]));
+        assert_eq!(rx.by_ref().take(2).collect::<Vec<_>>(), vec![0.25, 0.25]);
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn stop_with_handle() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<i16>(1, 48000);
+        let handle = tx.add_with_handle(SamplesBuffer::new(1, 48000, vec![10i16; 100]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16; 100]));
+        assert_eq!(rx.next(), Some(11));
+        handle.stop();
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(tx.active_sources(), 1);
+    }
+
+    #[test]
+    fn oneshot_is_reaped() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 1000);
+        let first = tx.add_oneshot(SamplesBuffer::new(1, 1000, vec![0.5f32; 4]));
+        drop(tx.add_oneshot(SamplesBuffer::new(1, 1000, vec![0.25f32; 100])));
+        assert_eq!(rx.by_ref().take(4).collect::<Vec<_>>(), vec![0.75; 4]);
+        assert_eq!(rx.next(), Some(0.25));
+        assert!(first.is_finished());
+        assert_eq!(tx.active_sources(), 1);
+    }
+
+    #[test]
+    fn oneshot_stop_and_volume() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 1000);
+        let handle = tx.add_oneshot(SamplesBuffer::new(1, 1000, vec![0.5f32; 100]));
+        assert_eq!(rx.next(), Some(0.5));
+        handle.set_volume(0.5);
+        assert_eq!(rx.next(), Some(0.25));
+        handle.stop();
+        assert_eq!(rx.next(), None);
+        assert!(handle.is_finished());
+        assert_eq!(tx.active_sources(), 0);
+    }
+
+    #[test]
+    fn voice_limit_drops_quietest() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 1000);
+        // levels are only measured while there is a limit
+        tx.set_max_voices(Some(3));
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.5f32; 2000]));
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.01f32; 2000]));
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.2f32; 2000]));
+        // long enough for the levels of all three to be known
+        rx.by_ref().take(1000).for_each(drop);
+
+        tx.set_max_voices(Some(2));
+        rx.by_ref().take(20).for_each(drop);
+        assert_eq!(tx.active_sources(), 2);
+        assert!((rx.next().unwrap() - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn active_labels() {
+        let (tx, mut rx) = dynamic_mixer::mixer::<f32>(1, 1000);
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.1f32; 10]).labeled("music"));
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.1f32; 5]));
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.1f32; 2]).labeled("click"));
+        assert_eq!(tx.active_labels(), vec!["music", "click"]);
+
+        rx.by_ref().take(5).for_each(drop);
+        assert_eq!(tx.active_labels(), vec!["music"]);
+        rx.by_ref().take(10).for_each(drop);
+        assert!(tx.active_labels().is_empty());
+    }
+
+    #[test]
+    fn fixed_headroom() {
+        use crate::dynamic_mixer::Headroom;
+
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 1000);
+        tx.set_headroom(Headroom::Decibels(20.0));
+        assert_eq!(tx.headroom(), Headroom::Decibels(20.0));
+        tx.set_headroom(Headroom::Decibels(-6.5));
+        assert_eq!(tx.headroom(), Headroom::Decibels(-6.5));
+        tx.set_headroom(Headroom::Decibels(20.0));
+        tx.add(SamplesBuffer::new(1, 1000, vec![1.0f32; 20]));
+        tx.add(SamplesBuffer::new(1, 1000, vec![1.0f32; 20]));
+
+        let samples: Vec<f32> = rx.by_ref().collect();
+        // glides from full volume to a tenth over 10ms
+        assert!(samples[..10].windows(2).all(|w| w[1] < w[0]));
+        assert!(samples[10..].iter().all(|&s| (s - 0.2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn per_source_headroom() {
+        use crate::dynamic_mixer::Headroom;
+
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 1000);
+        tx.set_headroom(Headroom::PerSource);
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.8f32; 40]));
+        for _ in 0..10 {
+            assert_eq!(rx.next(), Some(0.8));
+        }
+
+        tx.add(SamplesBuffer::new(1, 1000, vec![0.8f32; 20]));
+        let samples: Vec<f32> = rx.by_ref().take(20).collect();
+        assert!(samples.iter().all(|&s| s <= 1.6));
+        assert_eq!(samples.last(), Some(&0.8));
+
+        // back to full volume once the second source ended
+        let samples: Vec<f32> = rx.collect();
+        assert_eq!(samples.last(), Some(&0.8));
+    }
+
+    #[test]
+    fn active_sources() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        assert_eq!(tx.active_sources(), 0);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 1]));
+        // pending sources count too
+        assert_eq!(tx.active_sources(), 2);
+
+        rx.next();
+        assert_eq!(tx.active_sources(), 2);
+        rx.next();
+        assert_eq!(tx.active_sources(), 1);
+        rx.next();
+        assert_eq!(tx.active_sources(), 0);
+    }
+
+    #[test]
+    fn is_silent() {
+        // peak windows of 5 samples
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 100);
+        assert!(tx.is_silent());
+
+        let mut samples = vec![0.0f32; 5];
+        samples.extend([0.0, -0.5, 0.25, 0.0, 0.0]);
+        samples.extend([0.0; 5]);
+        tx.add(SamplesBuffer::new(1, 100, samples));
+
+        for _ in 0..5 {
+            rx.next();
+        }
+        assert!(tx.is_silent());
+        for _ in 0..5 {
+            rx.next();
+        }
+        assert!(!tx.is_silent());
+        assert_eq!(tx.recent_peak(), 0.5);
+        for _ in 0..5 {
+            rx.next();
+        }
+        assert!(tx.is_silent());
+        assert_eq!(tx.active_sources(), 1);
+    }
 }
@@ -0,0 +1,205 @@
+//! Mixing to fixed size frames for an encoder instead of to a device.
+//!
+//! With the `opus` feature the frames can be encoded to Opus packets with `OpusEncoder` and
+//! written to an Ogg stream with `OggOpusWriter`.
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use crate::source::Source;
+
+#[cfg(feature = "opus")]
+pub use self::opus::{OggOpusWriter, OpusEncoder, OpusError};
+
+#[cfg(feature = "opus")]
+mod opus;
+
+/// The sample rate of the frames of an [`EncodedSink`], the rate Opus works at.
+pub const SAMPLE_RATE: u32 = 48_000;
+
+/// The length of the frames of an [`EncodedSink`], the frame size Opus uses by default.
+pub const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// Mixes sounds like an output stream does, but instead of playing the result hands it out
+/// in fixed size frames to an encoder, for example to send it over a network as Opus packets.
+///
+/// Sounds are added with [`controller`](EncodedSink::controller) and resampled to
+/// [`SAMPLE_RATE`]. Every call to [`next_packet`](EncodedSink::next_packet) mixes
+/// [`FRAME_DURATION`] of audio, interleaved, and returns what the encoder made of it. The
+/// sink does not keep time itself: call it as often as the packets are needed, or
+/// [`spawn`](EncodedSink::spawn) it to get the packets over a channel as they play. When
+/// nothing plays the frames are silent.
+///
+/// The encoder is any [`FrameEncoder`], a closure taking the frame will do. With the `opus`
+/// feature `EncodedSink::new_opus` builds a sink that makes Opus packets.
+///
+/// ```
+/// use rodio::source::{SineWave, Source};
+/// use rodio::EncodedSink;
+/// use std::time::Duration;
+///
+/// let mut sink = EncodedSink::new(2, |frame: &[f32]| frame.iter().map(|s| s.abs()).sum::<f32>());
+/// sink.controller()
+///     .add(SineWave::new(440.0).take_duration(Duration::from_secs(1)));
+/// let loudness = sink.next_packet();
+/// # assert!(loudness > 0.0);
+/// ```
+pub struct EncodedSink<E> {
+    controller: Arc<DynamicMixerController<f32>>,
+    mixer: DynamicMixer<f32>,
+    frame: Vec<f32>,
+    frame_len: usize,
+    encoder: E,
+}
+
+/// Turns the frames of an [`EncodedSink`] into packets.
+///
+/// Implemented for closures taking a frame, and for `OpusEncoder` with the `opus` feature.
+pub trait FrameEncoder {
+    /// What a frame is encoded into.
+    type Packet;
+
+    /// Encodes a frame of interleaved samples.
+    fn encode(&mut self, frame: &[f32]) -> Self::Packet;
+}
+
+impl<F, P> FrameEncoder for F
+where
+    F: FnMut(&[f32]) -> P,
+{
+    type Packet = P;
+
+    #[inline]
+    fn encode(&mut self, frame: &[f32]) -> P {
+        self(frame)
+    }
+}
+
+impl<E> EncodedSink<E>
+where
+    E: FrameEncoder,
+{
+    /// Builds a sink producing frames with `channels` channels, each passed to `encoder`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is zero.
+    pub fn new(channels: u16, encoder: E) -> EncodedSink<E> {
+        let (controller, mixer) = dynamic_mixer::mixer(channels, SAMPLE_RATE);
+        let frames = SAMPLE_RATE as u128 * FRAME_DURATION.as_millis() / 1000;
+        let frame_len = frames as usize * channels as usize;
+        EncodedSink {
+            controller,
+            mixer,
+            frame: vec![0.0; frame_len],
+            frame_len,
+            encoder,
+        }
+    }
+
+    /// Returns the controller of the mixer, to add sounds to.
+    #[inline]
+    pub fn controller(&self) -> &Arc<DynamicMixerController<f32>> {
+        &self.controller
+    }
+
+    /// Returns the number of channels of the frames.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    /// Returns the number of samples in a frame, over all channels.
+    #[inline]
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Mixes the next frame and returns what the encoder made of it.
+    pub fn next_packet(&mut self) -> E::Packet {
+        self.mixer.fill_output(&mut self.frame);
+        self.encoder.encode(&self.frame)
+    }
+
+    /// Moves the sink to a new thread that makes a packet every [`FRAME_DURATION`], the
+    /// pace at which they play, and sends them over the returned channel. Keep the
+    /// [`controller`](EncodedSink::controller) to add sounds first, the thread stops once
+    /// the receiver is dropped.
+    pub fn spawn(mut self) -> Receiver<E::Packet>
+    where
+        E: Send + 'static,
+        E::Packet: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut deadline = Instant::now();
+            while tx.send(self.next_packet()).is_ok() {
+                deadline += FRAME_DURATION;
+                if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                    thread::sleep(wait);
+                }
+            }
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::EncodedSink;
+
+    #[test]
+    fn frames_are_resampled_and_padded() {
+        let mut sink = EncodedSink::new(2, |frame: &[f32]| frame.to_vec());
+        assert_eq!(sink.frame_len(), 1920);
+
+        // 30ms of mono at 24kHz, played on both channels at 48kHz
+        sink.controller()
+            .add(SamplesBuffer::new(1, 24_000, vec![0.5f32; 720]));
+        let first = sink.next_packet();
+        assert_eq!(first.len(), 1920);
+        assert!(first.iter().all(|&sample| sample == 0.5));
+
+        let second = sink.next_packet();
+        assert_eq!(second.len(), 1920);
+        // the resampler fades out over the last input frame
+        assert!(second[..956].iter().all(|&sample| sample == 0.5));
+        assert!(second[960..].iter().all(|&sample| sample == 0.0));
+
+        assert_eq!(sink.next_packet(), vec![0.0; 1920]);
+    }
+
+    #[test]
+    fn sources_start_in_channel_step() {
+        let mut sink = EncodedSink::new(2, |frame: &[f32]| frame.to_vec());
+        let stereo =
+            || SamplesBuffer::new(2, 48_000, (1..=6).map(|n| n as f32).collect::<Vec<_>>());
+
+        // added after an idle frame
+        assert_eq!(sink.next_packet(), vec![0.0; 1920]);
+        sink.controller().add(stereo());
+        let frame = sink.next_packet();
+        assert_eq!(frame[..8], [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 0.0, 0.0]);
+
+        // added after a frame in which a source ended
+        sink.controller().add(stereo());
+        let frame = sink.next_packet();
+        assert_eq!(frame[..8], [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn spawned_sink_sends_packets() {
+        let sink = EncodedSink::new(1, |frame: &[f32]| frame.iter().sum::<f32>());
+        let controller = sink.controller().clone();
+        controller.add(SamplesBuffer::new(1, 48_000, vec![1.0f32; 1200]));
+        let packets = sink.spawn();
+
+        assert_eq!(packets.recv().unwrap(), 960.0);
+        assert_eq!(packets.recv().unwrap(), 240.0);
+        assert_eq!(packets.recv().unwrap(), 0.0);
+    }
+}
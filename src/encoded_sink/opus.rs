@@ -0,0 +1,206 @@
+use std::io::{self, Write};
+
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus_rs::Application;
+
+use super::{EncodedSink, FrameEncoder, FRAME_DURATION, SAMPLE_RATE};
+
+// Room for the largest packet libopus makes of a single frame.
+const MAX_PACKET_LEN: usize = 4000;
+
+// Samples per channel the encoder delays its input by at 48kHz, a decoder drops them.
+const PRE_SKIP: u16 = 312;
+
+/// Error returned when Opus can not encode, with the reason given by the encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Opus encoder failed: {0}")]
+pub struct OpusError(&'static str);
+
+/// Encodes the frames of an [`EncodedSink`] to Opus packets, one packet per frame.
+///
+/// Opus supports mono and stereo. The encoder is tuned for music and mixed sounds, at the
+/// default bitrate of the encoder until [`set_bitrate`](OpusEncoder::set_bitrate) is called.
+pub struct OpusEncoder {
+    encoder: opus_rs::OpusEncoder,
+    channels: u16,
+    packet: Vec<u8>,
+}
+
+impl OpusEncoder {
+    /// Builds an encoder for frames with `channels` channels at [`SAMPLE_RATE`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `channels` is not 1 or 2.
+    pub fn new(channels: u16) -> Result<OpusEncoder, OpusError> {
+        let encoder =
+            opus_rs::OpusEncoder::new(SAMPLE_RATE as i32, channels as usize, Application::Audio)
+                .map_err(OpusError)?;
+        Ok(OpusEncoder {
+            encoder,
+            channels,
+            packet: vec![0; MAX_PACKET_LEN],
+        })
+    }
+
+    /// Returns the number of channels of the frames.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Changes the bitrate the packets are encoded at, in bits per second.
+    #[inline]
+    pub fn set_bitrate(&mut self, bits_per_second: u32) {
+        self.encoder.bitrate_bps = bits_per_second.min(i32::MAX as u32) as i32;
+    }
+}
+
+impl FrameEncoder for OpusEncoder {
+    type Packet = Result<Vec<u8>, OpusError>;
+
+    fn encode(&mut self, frame: &[f32]) -> Result<Vec<u8>, OpusError> {
+        let frame_size = frame.len() / self.channels as usize;
+        let len = self
+            .encoder
+            .encode(frame, frame_size, &mut self.packet)
+            .map_err(OpusError)?;
+        Ok(self.packet[..len].to_vec())
+    }
+}
+
+impl EncodedSink<OpusEncoder> {
+    /// Builds a sink that encodes its frames to Opus packets, see [`OpusEncoder`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `channels` is not 1 or 2.
+    pub fn new_opus(channels: u16) -> Result<EncodedSink<OpusEncoder>, OpusError> {
+        let encoder = OpusEncoder::new(channels)?;
+        Ok(EncodedSink::new(channels, encoder))
+    }
+}
+
+/// Writes the packets of an [`OpusEncoder`] to an Ogg Opus stream, the format of `.opus`
+/// files and of Opus streamed over HTTP.
+///
+/// Every packet ends an Ogg page, so it is written out right away. Call
+/// [`finish`](OggOpusWriter::finish) after the last packet to end the stream.
+pub struct OggOpusWriter<W: Write> {
+    writer: PacketWriter<W>,
+    serial: u32,
+    // Samples per channel up to the end of the last packet written.
+    granule: u64,
+    // Held back until the next packet, the last one has to end the stream.
+    pending: Option<Vec<u8>>,
+}
+
+impl<W: Write> OggOpusWriter<W> {
+    /// Starts a stream with `channels` channels, writing the Opus headers to `writer`.
+    ///
+    /// `serial` tells the stream apart from others in the same Ogg file, any number does
+    /// for a file with a single stream.
+    pub fn new(writer: W, channels: u16, serial: u32) -> io::Result<OggOpusWriter<W>> {
+        let mut writer = PacketWriter::new(writer);
+
+        let mut head = b"OpusHead".to_vec();
+        head.push(1); // version
+        head.push(channels as u8);
+        head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+        head.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+        head.push(0); // channel mapping family, mono or stereo
+        writer.write_packet(head.into(), serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        let vendor = b"rodio";
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes()); // no user comments
+        writer.write_packet(tags.into(), serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        Ok(OggOpusWriter {
+            writer,
+            serial,
+            granule: 0,
+            pending: None,
+        })
+    }
+
+    /// Adds a packet of [`FRAME_DURATION`] to the stream.
+    pub fn write_packet(&mut self, packet: Vec<u8>) -> io::Result<()> {
+        if let Some(previous) = self.pending.replace(packet) {
+            self.write(previous, PacketWriteEndInfo::EndPage)?;
+        }
+        Ok(())
+    }
+
+    /// Ends the stream and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(last) = self.pending.take() {
+            self.write(last, PacketWriteEndInfo::EndStream)?;
+        }
+        Ok(self.writer.into_inner())
+    }
+
+    fn write(&mut self, packet: Vec<u8>, end: PacketWriteEndInfo) -> io::Result<()> {
+        self.granule += SAMPLE_RATE as u64 * FRAME_DURATION.as_millis() as u64 / 1000;
+        self.writer
+            .write_packet(packet.into(), self.serial, end, self.granule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use ogg::reading::PacketReader;
+
+    use crate::source::{SineWave, Source};
+    use crate::EncodedSink;
+
+    use super::{OggOpusWriter, OpusEncoder};
+
+    #[test]
+    fn only_mono_and_stereo() {
+        assert!(OpusEncoder::new(1).is_ok());
+        assert!(OpusEncoder::new(3).is_err());
+    }
+
+    #[test]
+    fn sine_round_trips_through_ogg_opus() {
+        let mut sink = EncodedSink::new_opus(2).unwrap();
+        sink.controller()
+            .add(SineWave::new(440.0).take_duration(Duration::from_millis(200)));
+        let mut writer = OggOpusWriter::new(Vec::new(), 2, 7).unwrap();
+        for _ in 0..10 {
+            writer.write_packet(sink.next_packet().unwrap()).unwrap();
+        }
+        let data = writer.finish().unwrap();
+
+        let mut reader = PacketReader::new(Cursor::new(data));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        assert_eq!(head.data[9], 2);
+        let tags = reader.read_packet_expected().unwrap();
+        assert_eq!(&tags.data[..8], b"OpusTags");
+
+        let mut decoder = opus_rs::OpusDecoder::new(48_000, 2).unwrap();
+        let mut pcm = vec![0.0f32; 1920];
+        let mut energy = 0.0;
+        let mut packets = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            packets += 1;
+            assert_eq!(packet.absgp_page(), packets * 960);
+            assert_eq!(packet.last_in_stream(), packets == 10);
+            let decoded = decoder.decode(&packet.data, 960, &mut pcm).unwrap();
+            assert_eq!(decoded, 960);
+            energy += pcm.iter().map(|sample| sample * sample).sum::<f32>();
+        }
+        assert_eq!(packets, 10);
+        // a sine at full scale has an RMS of 0.7, most of it survives the encoding
+        let rms = (energy / (10.0 * 1920.0)).sqrt();
+        assert!(rms > 0.5 && rms < 0.8, "{rms}");
+    }
+}
@@ -110,6 +110,12 @@
 //! The "tracing" feature replaces the print to stderr when a stream error happens with a
 //! recording an error event with tracing.
 //!
+//! ### Feature "opus"
+//!
+//! The "opus" feature encodes the frames of an [`EncodedSink`] to Opus packets and writes
+//! them to Ogg streams, see the [`encoded_sink`] module. The encoder is written in Rust, it
+//! needs no system library.
+//!
 //! ## How it works under the hood
 //!
 //! Rodio spawns a background thread that is dedicated to reading from the sources and sending
@@ -128,6 +134,7 @@ pub use cpal::{
 };
 
 mod conversions;
+mod cue_player;
 mod sink;
 mod spatial_sink;
 mod stream;
@@ -135,13 +142,18 @@ mod stream;
 pub mod buffer;
 pub mod decoder;
 pub mod dynamic_mixer;
+pub mod encoded_sink;
 pub mod queue;
 pub mod source;
 pub mod static_buffer;
 
-pub use crate::conversions::Sample;
+pub use crate::conversions::{FromSample, Sample};
+pub use crate::cue_player::CuePlayer;
 pub use crate::decoder::Decoder;
-pub use crate::sink::Sink;
+pub use crate::encoded_sink::EncodedSink;
+pub use crate::sink::{Sink, SoundStatus, SoundToken};
 pub use crate::source::Source;
-pub use crate::spatial_sink::SpatialSink;
-pub use crate::stream::{OutputStream, OutputStreamHandle, PlayError, StreamError};
+pub use crate::spatial_sink::{SpatialListener, SpatialSink};
+pub use crate::stream::{
+    CallbackStats, DefaultDeviceWatcher, OutputStream, OutputStreamHandle, PlayError, StreamError,
+};
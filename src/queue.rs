@@ -117,6 +117,9 @@ pub struct SourcesQueueOutput<S> {
 }
 
 const THRESHOLD: usize = 512;
+// Format of the silence played while a queue that is kept alive is empty.
+const SILENCE_CHANNELS: u16 = 1;
+const SILENCE_SAMPLE_RATE: u32 = 44100;
 impl<S> Source for SourcesQueueOutput<S>
 where
     S: Sample + Send + 'static,
@@ -160,12 +163,18 @@ where
 
     #[inline]
     fn channels(&self) -> u16 {
-        self.current.channels()
+        match self.next_format() {
+            Some((channels, _)) => channels,
+            None => self.current.channels(),
+        }
     }
 
     #[inline]
     fn sample_rate(&self) -> u32 {
-        self.current.sample_rate()
+        match self.next_format() {
+            Some((_, sample_rate)) => sample_rate,
+            None => self.current.sample_rate(),
+        }
     }
 
     #[inline]
@@ -186,6 +195,26 @@ where
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.current.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.current.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.current.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.current.label()
+    }
 }
 
 impl<S> Iterator for SourcesQueueOutput<S>
@@ -220,6 +249,21 @@ impl<S> SourcesQueueOutput<S>
 where
     S: Sample + Send + 'static,
 {
+    // When `current` has no samples left the next frame comes from the next sound, which
+    // might have a different format. Returns the channels and sample rate of that sound.
+    fn next_format(&self) -> Option<(u16, u32)> {
+        if self.current.current_frame_len() != Some(0) {
+            return None;
+        }
+        if let Some((next, _)) = self.input.next_sounds.lock().unwrap().first() {
+            Some((next.channels(), next.sample_rate()))
+        } else if self.input.keep_alive_if_empty.load(Ordering::Acquire) {
+            Some((SILENCE_CHANNELS, SILENCE_SAMPLE_RATE))
+        } else {
+            None
+        }
+    }
+
     // Called when `current` is empty and we must jump to the next element.
     // Returns `Ok` if the sound should continue playing, or an error if it should stop.
     //
@@ -233,7 +277,11 @@ where
             let mut next = self.input.next_sounds.lock().unwrap();
 
             if next.len() == 0 {
-                let silence = Box::new(Zero::<S>::new_samples(1, 44100, THRESHOLD)) as Box<_>;
+                let silence = Box::new(Zero::<S>::new_samples(
+                    SILENCE_CHANNELS,
+                    SILENCE_SAMPLE_RATE,
+                    THRESHOLD,
+                )) as Box<_>;
                 if self.input.keep_alive_if_empty.load(Ordering::Acquire) {
                     // Play a short silence in order to avoid spinlocking.
                     (silence, None)
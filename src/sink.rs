@@ -1,5 +1,5 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::Duration;
 
 #[cfg(feature = "crossbeam-channel")]
@@ -7,9 +7,10 @@ use crossbeam_channel::{Receiver, Sender};
 #[cfg(not(feature = "crossbeam-channel"))]
 use std::sync::mpsc::{Receiver, Sender};
 
-use crate::source::SeekError;
+use crate::dynamic_mixer::{DynamicMixerController, SharedLabel};
+use crate::source::{FormatError, SeekError};
 use crate::stream::{OutputStreamHandle, PlayError};
-use crate::{queue, source::Done, Sample, Source};
+use crate::{queue, Sample, Source};
 use cpal::FromSample;
 
 /// Handle to a device that outputs sounds.
@@ -18,21 +19,26 @@ use cpal::FromSample;
 /// playing.
 pub struct Sink {
     queue_tx: Arc<queue::SourcesQueueInput<f32>>,
-    sleep_until_end: Mutex<Option<Receiver<()>>>,
 
     controls: Arc<Controls>,
-    sound_count: Arc<AtomicUsize>,
+    sound_count: Arc<SoundCount>,
 
     detached: bool,
 }
 
 struct SeekOrder {
-    pos: Duration,
-    feedback: Sender<Result<(), SeekError>>,
+    target: SeekTarget,
+    feedback: Sender<Result<Duration, SeekError>>,
+}
+
+/// Where a `SeekOrder` seeks to.
+enum SeekTarget {
+    Position(Duration),
+    Fraction(f32),
 }
 
 impl SeekOrder {
-    fn new(pos: Duration) -> (Self, Receiver<Result<(), SeekError>>) {
+    fn new(target: SeekTarget) -> (Self, Receiver<Result<Duration, SeekError>>) {
         #[cfg(not(feature = "crossbeam-channel"))]
         let (tx, rx) = {
             use std::sync::mpsc;
@@ -44,7 +50,13 @@ impl SeekOrder {
             use crossbeam_channel::bounded;
             bounded(1)
         };
-        (Self { pos, feedback: tx }, rx)
+        (
+            Self {
+                target,
+                feedback: tx,
+            },
+            rx,
+        )
     }
 
     fn attempt<S>(self, maybe_seekable: &mut S)
@@ -52,19 +64,216 @@ impl SeekOrder {
         S: Source,
         S::Item: Sample + Send,
     {
-        let res = maybe_seekable.try_seek(self.pos);
+        let res = match self.target {
+            SeekTarget::Position(pos) => maybe_seekable.try_seek(pos).map(|()| pos),
+            SeekTarget::Fraction(fraction) => maybe_seekable.try_seek_fraction(fraction),
+        };
         let _ignore_receiver_dropped = self.feedback.send(res);
     }
 }
 
+/// Number of sounds in the queue. Threads can wait for it to drop to zero.
+#[derive(Default)]
+struct SoundCount {
+    count: Mutex<usize>,
+    emptied: Condvar,
+}
+
+impl SoundCount {
+    fn get(&self) -> usize {
+        *self.count.lock().unwrap()
+    }
+
+    fn wait_until_empty(&self) {
+        let count = self.count.lock().unwrap();
+        let _count = self.emptied.wait_while(count, |count| *count > 0).unwrap();
+    }
+}
+
+/// Counts a sound for as long as it lives. Dropped once the sound has ended, or together with
+/// the sound if it never got to play to the end, for example because the output stream was
+/// dropped.
+struct CountedSound(Arc<SoundCount>);
+
+impl CountedSound {
+    fn new(sound_count: Arc<SoundCount>) -> Self {
+        *sound_count.count.lock().unwrap() += 1;
+        Self(sound_count)
+    }
+}
+
+impl Drop for CountedSound {
+    fn drop(&mut self) {
+        let mut count = self.0.count.lock().unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.0.emptied.notify_all();
+        }
+    }
+}
+
+/// Where a sound appended to a [`Sink`] is in its life, see [`SoundToken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundStatus {
+    /// Waiting for the sounds before it in the queue to end.
+    Queued,
+    /// Being played.
+    Playing,
+    /// Played to the end.
+    Finished,
+    /// Removed before it played to the end, by [`Sink::clear`], [`Sink::skip_one`] or
+    /// [`Sink::stop`], or because the sink or the output stream was dropped.
+    Cancelled,
+}
+
+impl SoundStatus {
+    /// Returns true if the sound is finished or cancelled, the status will not change anymore.
+    #[inline]
+    pub fn is_done(self) -> bool {
+        matches!(self, SoundStatus::Finished | SoundStatus::Cancelled)
+    }
+}
+
+/// Identifies a sound appended to a [`Sink`], returned by [`Sink::append`].
+///
+/// Threads can use it to find out whether the sound is playing, or to wait for it to start
+/// or end. For example to show subtitles in sync with lines of dialogue queued on a sink.
+/// Cloning the token is cheap, all clones refer to the same sound.
+#[derive(Clone)]
+pub struct SoundToken(Arc<SoundState>);
+
+struct SoundState {
+    status: Mutex<SoundStatus>,
+    changed: Condvar,
+}
+
+impl SoundToken {
+    fn new() -> Self {
+        SoundToken(Arc::new(SoundState {
+            status: Mutex::new(SoundStatus::Queued),
+            changed: Condvar::new(),
+        }))
+    }
+
+    /// Returns the current status of the sound.
+    #[inline]
+    pub fn status(&self) -> SoundStatus {
+        *self.0.status.lock().unwrap()
+    }
+
+    /// Blocks the current thread until the sound is no longer queued and returns its status.
+    ///
+    /// That is [`SoundStatus::Playing`] once the sound starts, but can also be
+    /// [`SoundStatus::Finished`] for very short sounds or [`SoundStatus::Cancelled`] if the
+    /// sound was removed from the queue before it got to play.
+    pub fn wait_until_start(&self) -> SoundStatus {
+        let status = self.0.status.lock().unwrap();
+        *self
+            .0
+            .changed
+            .wait_while(status, |status| *status == SoundStatus::Queued)
+            .unwrap()
+    }
+
+    /// Blocks the current thread until the sound is finished or cancelled and returns which
+    /// of the two it was.
+    pub fn wait_until_end(&self) -> SoundStatus {
+        let status = self.0.status.lock().unwrap();
+        *self
+            .0
+            .changed
+            .wait_while(status, |status| !status.is_done())
+            .unwrap()
+    }
+
+    // Statuses only move forward, once done a sound stays done.
+    fn set_status(&self, new: SoundStatus) {
+        let mut status = self.0.status.lock().unwrap();
+        if status.is_done() || *status == new {
+            return;
+        }
+        *status = new;
+        self.0.changed.notify_all();
+    }
+}
+
+impl PartialEq for SoundToken {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SoundToken {}
+
+impl std::fmt::Debug for SoundToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SoundToken").field(&self.status()).finish()
+    }
+}
+
+/// Lives as long as a sound and reports to its token how the sound ended. Unless told
+/// otherwise, dropping it means the sound was cancelled.
+struct SoundEnd {
+    token: SoundToken,
+    controls: Arc<Controls>,
+    status: SoundStatus,
+    _counted: CountedSound,
+}
+
+impl Drop for SoundEnd {
+    fn drop(&mut self) {
+        let mut current = self.controls.current.lock().unwrap();
+        if current.as_ref() == Some(&self.token) {
+            *current = None;
+        }
+        drop(current);
+        self.token.set_status(self.status);
+    }
+}
+
 struct Controls {
     pause: AtomicBool,
     volume: Mutex<f32>,
     stopped: AtomicBool,
     speed: Mutex<f32>,
+    // Set when the speed changed, holds how long to take to get to the new speed.
+    speed_glide: Mutex<Option<Duration>>,
     to_clear: Mutex<u32>,
     seek: Mutex<Option<SeekOrder>>,
     position: Mutex<Duration>,
+    // The token of the sound that is playing.
+    current: Mutex<Option<SoundToken>>,
+    // Set by `stop_after_fade`, the fade out before the sink stops.
+    fade_stop: Mutex<Option<FadeStop>>,
+    label: SharedLabel,
+}
+
+// How often the controls are applied to the sound that is playing.
+const ACCESS_PERIOD: Duration = Duration::from_millis(5);
+
+struct FadeStop {
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl Controls {
+    /// Advances a fade started by `stop_after_fade` by one access period, unless paused, and
+    /// returns the gain to glide to by the end of the period, `None` without a fade. Stops
+    /// the sink once the fade is complete, the next access period then ends the sound.
+    fn advance_fade(&self) -> Option<f32> {
+        let mut fade_stop = self.fade_stop.lock().unwrap();
+        let fade = fade_stop.as_mut()?;
+        if !self.pause.load(Ordering::SeqCst) {
+            fade.elapsed += ACCESS_PERIOD;
+        }
+        if fade.elapsed >= fade.duration {
+            *fade_stop = None;
+            self.stopped.store(true, Ordering::SeqCst);
+            return Some(0.0);
+        }
+        Some(1.0 - fade.elapsed.as_secs_f32() / fade.duration.as_secs_f32())
+    }
 }
 
 impl Sink {
@@ -72,10 +281,23 @@ impl Sink {
     #[inline]
     pub fn try_new(stream: &OutputStreamHandle) -> Result<Sink, PlayError> {
         let (sink, queue_rx) = Sink::new_idle();
-        stream.play_raw(queue_rx)?;
+        stream.play_raw_labeled(queue_rx, sink.controls.label.clone())?;
         Ok(sink)
     }
 
+    /// Builds a new `Sink` that plays into `mixer` instead of an output device.
+    ///
+    /// Combined with a mixer from [`mixer`](crate::dynamic_mixer::mixer) this renders audio
+    /// at any sample rate and channel count, for example to export a mix to a file. The
+    /// sounds appended to the sink are converted to the format of the mixer and the mixed
+    /// result is read from the mixer output.
+    #[inline]
+    pub fn connect_new(mixer: &DynamicMixerController<f32>) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        let _rejected = mixer.try_add_labeled(queue_rx, sink.controls.label.clone());
+        sink
+    }
+
     /// Builds a new `Sink`.
     #[inline]
     pub fn new_idle() -> (Sink, queue::SourcesQueueOutput<f32>) {
@@ -83,39 +305,71 @@ impl Sink {
 
         let sink = Sink {
             queue_tx,
-            sleep_until_end: Mutex::new(None),
             controls: Arc::new(Controls {
                 pause: AtomicBool::new(false),
                 volume: Mutex::new(1.0),
                 stopped: AtomicBool::new(false),
                 speed: Mutex::new(1.0),
+                speed_glide: Mutex::new(None),
                 to_clear: Mutex::new(0),
                 seek: Mutex::new(None),
                 position: Mutex::new(Duration::ZERO),
+                current: Mutex::new(None),
+                fade_stop: Mutex::new(None),
+                label: Arc::new(Mutex::new(None)),
             }),
-            sound_count: Arc::new(AtomicUsize::new(0)),
+            sound_count: Arc::new(SoundCount::default()),
             detached: false,
         };
         (sink, queue_rx)
     }
 
     /// Appends a sound to the queue of sounds to play.
+    ///
+    /// Returns a token to follow the sound with, see [`SoundToken`]. It can be ignored.
+    ///
+    /// Sounds that report zero channels or an invalid sample rate can not be played. They are
+    /// dropped and their token is [`SoundStatus::Cancelled`] right away, see
+    /// [`try_append`](Sink::try_append) to get the reason.
     #[inline]
-    pub fn append<S>(&self, source: S)
+    pub fn append<S>(&self, source: S) -> SoundToken
     where
         S: Source + Send + 'static,
         f32: FromSample<S::Item>,
         S::Item: Sample + Send,
     {
+        self.try_append(source).unwrap_or_else(|_| {
+            let token = SoundToken::new();
+            token.set_status(SoundStatus::Cancelled);
+            token
+        })
+    }
+
+    /// Appends a sound to the queue of sounds to play, see [`append`](Sink::append).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and drops the sound, if it reports zero channels, a sample rate of
+    /// zero or one above [`FormatError::MAX_SAMPLE_RATE`].
+    pub fn try_append<S>(&self, source: S) -> Result<SoundToken, FormatError>
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        FormatError::check_new(&source)?;
+
         // Wait for queue to flush then resume stopped playback
         if self.controls.stopped.load(Ordering::SeqCst) {
-            if self.sound_count.load(Ordering::SeqCst) > 0 {
+            if self.sound_count.get() > 0 {
                 self.sleep_until_end();
             }
             self.controls.stopped.store(false, Ordering::SeqCst);
         }
 
         let controls = self.controls.clone();
+        let token = SoundToken::new();
+        let this_sound = token.clone();
 
         let start_played = AtomicBool::new(false);
 
@@ -128,15 +382,17 @@ impl Sink {
             .skippable()
             .stoppable()
             // if you change the duration update the docs for try_seek!
-            .periodic_access(Duration::from_millis(5), move |src| {
+            .periodic_access(ACCESS_PERIOD, move |src| {
                 if controls.stopped.load(Ordering::SeqCst) {
                     src.stop();
+                    this_sound.set_status(SoundStatus::Cancelled);
                     *controls.position.lock().unwrap() = Duration::ZERO;
                 }
                 {
                     let mut to_clear = controls.to_clear.lock().unwrap();
                     if *to_clear > 0 {
                         src.inner_mut().skip();
+                        this_sound.set_status(SoundStatus::Cancelled);
                         *to_clear -= 1;
                         *controls.position.lock().unwrap() = Duration::ZERO;
                     } else {
@@ -144,22 +400,67 @@ impl Sink {
                     }
                 }
                 let amp = src.inner_mut().inner_mut();
-                amp.set_factor(*controls.volume.lock().unwrap());
+                let volume = *controls.volume.lock().unwrap();
+                match controls.advance_fade() {
+                    Some(gain) => amp.set_factor_smooth(volume * gain, ACCESS_PERIOD),
+                    None => amp.set_factor(volume),
+                }
                 amp.inner_mut()
                     .set_paused(controls.pause.load(Ordering::SeqCst));
-                amp.inner_mut()
-                    .inner_mut()
-                    .inner_mut()
-                    .set_factor(*controls.speed.lock().unwrap());
+                let speed = amp.inner_mut().inner_mut().inner_mut();
+                if let Some(glide) = controls.speed_glide.lock().unwrap().take() {
+                    speed.set_factor_smooth(*controls.speed.lock().unwrap(), glide);
+                } else if !speed.is_gliding() {
+                    speed.set_factor(*controls.speed.lock().unwrap());
+                }
                 if let Some(seek) = controls.seek.lock().unwrap().take() {
                     seek.attempt(amp)
                 }
-                start_played.store(true, Ordering::SeqCst);
+                if !start_played.swap(true, Ordering::SeqCst)
+                    && this_sound.status() == SoundStatus::Queued
+                {
+                    this_sound.set_status(SoundStatus::Playing);
+                    *controls.current.lock().unwrap() = Some(this_sound.clone());
+                }
             })
             .convert_samples();
-        self.sound_count.fetch_add(1, Ordering::Relaxed);
-        let source = Done::new(source, self.sound_count.clone());
-        *self.sleep_until_end.lock().unwrap() = Some(self.queue_tx.append_with_signal(source));
+        let mut end = SoundEnd {
+            token: token.clone(),
+            controls: self.controls.clone(),
+            status: SoundStatus::Cancelled,
+            _counted: CountedSound::new(self.sound_count.clone()),
+        };
+        let source = source.inspect_end(move || {
+            end.status = SoundStatus::Finished;
+            drop(end)
+        });
+        self.queue_tx.append(source);
+        Ok(token)
+    }
+
+    /// Gives the sink a name to tell it apart while debugging. The mixer it plays on lists it
+    /// in [`active_labels`](DynamicMixerController::active_labels).
+    ///
+    /// The label belongs to the sink, not to the sounds appended to it, so it stays the
+    /// same from one sound to the next.
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        *self.controls.label.lock().unwrap() = Some(label.to_owned());
+    }
+
+    /// Returns the name given with [`set_label`](Sink::set_label), if any.
+    #[inline]
+    pub fn label(&self) -> Option<String> {
+        self.controls.label.lock().unwrap().clone()
+    }
+
+    /// Returns the token of the sound that is playing, or `None` if no sound is.
+    ///
+    /// Compare it with the tokens returned by [`append`](Sink::append) to find out which
+    /// sound of a playlist is playing.
+    #[inline]
+    pub fn current_token(&self) -> Option<SoundToken> {
+        self.controls.current.lock().unwrap().clone()
     }
 
     /// Gets the volume of the sound.
@@ -184,6 +485,9 @@ impl Sink {
     ///
     /// The value `1.0` is the "normal" speed (unfiltered input). Any value other than `1.0` will
     /// change the play speed of the sound.
+    ///
+    /// While gliding to a new speed, see [`set_speed_smooth`](Sink::set_speed_smooth), this
+    /// returns the speed being glided to.
     #[inline]
     pub fn speed(&self) -> f32 {
         *self.controls.speed.lock().unwrap()
@@ -196,19 +500,37 @@ impl Sink {
     #[inline]
     pub fn set_speed(&self, value: f32) {
         *self.controls.speed.lock().unwrap() = value;
+        *self.controls.speed_glide.lock().unwrap() = Some(Duration::ZERO);
+    }
+
+    /// Glides the speed of the sound from its current value to `value` over `duration`.
+    ///
+    /// Both the pitch and the tempo change gradually, like a record player that is being
+    /// slowed down. Gliding to a speed of (almost) zero is supported. A call to
+    /// [`set_speed`](Sink::set_speed) during the glide stops it and jumps to the new speed.
+    ///
+    /// The glide applies to the sound that is playing. Sounds that start later play at
+    /// `value` right away.
+    #[inline]
+    pub fn set_speed_smooth(&self, value: f32, duration: Duration) {
+        *self.controls.speed.lock().unwrap() = value;
+        *self.controls.speed_glide.lock().unwrap() = Some(duration);
     }
 
     /// Resumes playback of a paused sink.
     ///
-    /// No effect if not paused.
+    /// Also cancels a fade out started with [`stop_after_fade`](Sink::stop_after_fade), the
+    /// sound goes back to full volume and keeps playing. No other effect if not paused.
     #[inline]
     pub fn play(&self) {
         self.controls.pause.store(false, Ordering::SeqCst);
+        *self.controls.fade_stop.lock().unwrap() = None;
     }
 
     // There is no `can_seek()` method as it is impossible to use correctly. Between
     // checking if a source supports seeking and actually seeking the sink can
-    // switch to a new source.
+    // switch to a new source. Ask the source with `Source::is_seekable` before
+    // appending it instead.
 
     /// Attempts to seek to a given position in the current source.
     ///
@@ -229,23 +551,43 @@ impl Sink {
     /// When seeking beyond the end of a source this
     /// function might return an error if the duration of the source is not known.
     pub fn try_seek(&self, pos: Duration) -> Result<(), SeekError> {
-        let (order, feedback) = SeekOrder::new(pos);
+        self.seek(SeekTarget::Position(pos))?;
+        Ok(())
+    }
+
+    /// Seeks to a fraction of the total duration of the current source, from `0.0` for the
+    /// start to `1.0` for the end, and returns the position that was reached. Meant for
+    /// scrub bars, see [`Source::try_seek_fraction`].
+    ///
+    /// This blocks between 0 and ~5 milliseconds. Returns `Ok(Duration::ZERO)` without
+    /// seeking if no sound is playing.
+    ///
+    /// # Errors
+    /// Returns [`SeekError::UnknownDuration`] if the current source does not know its total
+    /// duration, otherwise the same as for [`try_seek`](Sink::try_seek).
+    pub fn try_seek_fraction(&self, fraction: f32) -> Result<Duration, SeekError> {
+        self.seek(SeekTarget::Fraction(fraction))
+    }
+
+    fn seek(&self, target: SeekTarget) -> Result<Duration, SeekError> {
+        let (order, feedback) = SeekOrder::new(target);
         *self.controls.seek.lock().unwrap() = Some(order);
 
-        if self.sound_count.load(Ordering::Acquire) == 0 {
+        if self.sound_count.get() == 0 {
             // No sound is playing, seek will not be performed
-            return Ok(());
+            return Ok(Duration::ZERO);
         }
 
         match feedback.recv() {
-            Ok(seek_res) => {
+            Ok(Ok(pos)) => {
                 *self.controls.position.lock().unwrap() = pos;
-                seek_res
+                Ok(pos)
             }
+            Ok(Err(err)) => Err(err),
             // The feedback channel closed. Probably another seekorder was set
             // invalidating this one and closing the feedback channel
             // ... or the audio thread panicked.
-            Err(_) => Ok(()),
+            Err(_) => Ok(Duration::ZERO),
         }
     }
 
@@ -270,7 +612,8 @@ impl Sink {
     ///
     /// See `pause()` for information about pausing a `Sink`.
     pub fn clear(&self) {
-        let len = self.sound_count.load(Ordering::SeqCst) as u32;
+        *self.controls.fade_stop.lock().unwrap() = None;
+        let len = self.sound_count.get() as u32;
         *self.controls.to_clear.lock().unwrap() = len;
         self.sleep_until_end();
         self.pause();
@@ -282,7 +625,7 @@ impl Sink {
     /// it will play the next one. Otherwise, the `Sink` will finish as if
     /// it had finished playing a `Source` all the way through.
     pub fn skip_one(&self) {
-        let len = self.sound_count.load(Ordering::SeqCst) as u32;
+        let len = self.sound_count.get() as u32;
         let mut to_clear = self.controls.to_clear.lock().unwrap();
         if len > *to_clear {
             *to_clear += 1;
@@ -292,9 +635,28 @@ impl Sink {
     /// Stops the sink by emptying the queue.
     #[inline]
     pub fn stop(&self) {
+        *self.controls.fade_stop.lock().unwrap() = None;
         self.controls.stopped.store(true, Ordering::SeqCst);
     }
 
+    /// Fades the sound out to silence over `duration`, then stops the sink by emptying the
+    /// queue as [`stop`](Sink::stop) does.
+    ///
+    /// The fade is on top of the [`volume`](Sink::volume) and continues into the next sound
+    /// if the current one ends first. Pausing the sink holds the fade where it is, calling
+    /// [`play`](Sink::play) cancels it. Calling this again restarts the fade with the new
+    /// duration. If nothing is playing, or `duration` is zero, the sink stops right away.
+    pub fn stop_after_fade(&self, duration: Duration) {
+        if duration.is_zero() || self.empty() {
+            self.stop();
+            return;
+        }
+        *self.controls.fade_stop.lock().unwrap() = Some(FadeStop {
+            duration,
+            elapsed: Duration::ZERO,
+        });
+    }
+
     /// Destroys the sink without stopping the sounds that are still playing.
     #[inline]
     pub fn detach(mut self) {
@@ -302,11 +664,13 @@ impl Sink {
     }
 
     /// Sleeps the current thread until the sound ends.
+    ///
+    /// The thread is woken up when the last sound in the queue has finished playing, so
+    /// changing the speed or seeking while sleeping is fine. Sounds appended from other
+    /// threads in the mean time are waited for as well.
     #[inline]
     pub fn sleep_until_end(&self) {
-        if let Some(sleep_until_end) = self.sleep_until_end.lock().unwrap().take() {
-            let _ = sleep_until_end.recv();
-        }
+        self.sound_count.wait_until_empty();
     }
 
     /// Returns true if this sink has no more sounds to play.
@@ -319,7 +683,7 @@ impl Sink {
     #[allow(clippy::len_without_is_empty)]
     #[inline]
     pub fn len(&self) -> usize {
-        self.sound_count.load(Ordering::Relaxed)
+        self.sound_count.get()
     }
 
     /// Returns the position of the sound that's being played.
@@ -349,8 +713,11 @@ impl Drop for Sink {
 #[cfg(test)]
 mod tests {
     use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::sink::SoundStatus;
     use crate::{Sink, Source};
     use std::sync::atomic::Ordering;
+    use std::time::Duration;
 
     #[test]
     fn test_pause_and_stop() {
@@ -428,4 +795,217 @@ mod tests {
             assert_eq!(queue_rx.next(), src.next());
         }
     }
+
+    #[test]
+    fn test_stop_after_fade() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]));
+        sink.stop_after_fade(Duration::from_millis(100));
+
+        let fade: Vec<f32> = queue_rx.by_ref().take(100).collect();
+        // the gain changes every sample, not in steps of an access period
+        assert!(fade.windows(2).all(|w| w[1] < w[0] && w[0] - w[1] < 0.02));
+        assert!(fade[45..55].iter().all(|&s| s > 0.4 && s < 0.6));
+        assert!(fade[95..].iter().all(|&s| s < 0.06));
+
+        assert!(queue_rx.by_ref().take(20).all(|s| s == 0.0));
+        assert!(sink.empty());
+    }
+
+    #[test]
+    fn test_play_cancels_stop_after_fade() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]));
+        sink.stop_after_fade(Duration::from_millis(100));
+        queue_rx.by_ref().take(50).for_each(drop);
+
+        // a pause holds the fade
+        sink.pause();
+        queue_rx.by_ref().take(200).for_each(drop);
+        assert_eq!(sink.len(), 1);
+
+        sink.play();
+        let after: Vec<f32> = queue_rx.by_ref().take(200).collect();
+        assert!(after[10..].iter().all(|&s| s == 1.0));
+        assert_eq!(sink.len(), 1);
+    }
+
+    #[test]
+    fn test_label() {
+        let (tx, _rx) = dynamic_mixer::mixer::<f32>(2, 44100);
+        let sink = Sink::connect_new(&tx);
+        assert_eq!(sink.label(), None);
+        assert!(tx.active_labels().is_empty());
+
+        sink.set_label("music");
+        assert_eq!(sink.label().as_deref(), Some("music"));
+        assert_eq!(tx.active_labels(), vec!["music"]);
+    }
+
+    #[test]
+    fn test_set_speed_smooth() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![10i16; 1000]));
+        queue_rx.next();
+        assert_eq!(queue_rx.sample_rate(), 1000);
+
+        sink.set_speed_smooth(0.5, Duration::from_millis(100));
+        assert_eq!(sink.speed(), 0.5);
+
+        let mut rates = Vec::new();
+        for _ in 0..200 {
+            queue_rx.next();
+            rates.push(queue_rx.sample_rate());
+        }
+        assert!(rates.windows(2).all(|w| w[1] <= w[0]));
+        assert!(rates.iter().any(|&rate| rate > 500 && rate < 1000));
+        assert_eq!(rates.last(), Some(&500));
+
+        sink.set_speed(2.0);
+        for _ in 0..10 {
+            queue_rx.next();
+        }
+        assert_eq!(queue_rx.sample_rate(), 2000);
+    }
+
+    #[test]
+    fn test_sleep_until_end() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![10i16; 100]));
+        sink.append(SamplesBuffer::new(1, 1000, vec![10i16; 100]));
+        assert_eq!(sink.len(), 2);
+
+        std::thread::scope(|s| {
+            // two threads waiting at the same time are both woken up
+            let waiter = s.spawn(|| sink.sleep_until_end());
+            s.spawn(|| {
+                for _ in 0..50 {
+                    queue_rx.next();
+                }
+                sink.set_speed(3.0);
+                for _ in 0..1000 {
+                    queue_rx.next();
+                }
+            });
+            sink.sleep_until_end();
+            waiter.join().unwrap();
+        });
+
+        assert!(sink.empty());
+        // nothing left to wait for
+        sink.sleep_until_end();
+    }
+
+    #[test]
+    fn test_sleep_until_end_output_dropped() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        sink.append(SamplesBuffer::new(1, 1000, vec![10i16; 100]));
+        queue_rx.next();
+
+        std::thread::scope(|s| {
+            s.spawn(move || drop(queue_rx));
+            sink.sleep_until_end();
+        });
+        assert!(sink.empty());
+    }
+
+    #[test]
+    fn test_render_to_mixer() {
+        let (mixer, mut output) = dynamic_mixer::mixer::<f32>(2, 8000);
+        let sink = Sink::connect_new(&mixer);
+        assert_eq!(output.channels(), 2);
+        assert_eq!(output.sample_rate(), 8000);
+
+        // one second at twice the rate of the mixer
+        sink.append(SamplesBuffer::new(1, 16000, vec![0.5f32; 16000]));
+
+        let mut rendered = Vec::new();
+        while !sink.empty() {
+            rendered.push(output.next().unwrap());
+        }
+        // downsampled to exactly one second, nothing is cut off
+        assert!(rendered[..2 * 8000].iter().all(|&s| s == 0.5));
+        assert!(rendered[2 * 8000..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_tokens_follow_playback() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        let first = sink.append(SamplesBuffer::new(1, 1000, vec![1i16; 10]));
+        let second = sink.append(SamplesBuffer::new(1, 1000, vec![2i16; 10]));
+        assert_ne!(first, second);
+        assert_eq!(first.status(), SoundStatus::Queued);
+        assert_eq!(sink.current_token(), None);
+
+        queue_rx.next();
+        assert_eq!(first.wait_until_start(), SoundStatus::Playing);
+        assert_eq!(sink.current_token(), Some(first.clone()));
+        assert_eq!(second.status(), SoundStatus::Queued);
+
+        while second.status() == SoundStatus::Queued {
+            queue_rx.next();
+        }
+        assert_eq!(first.wait_until_end(), SoundStatus::Finished);
+        assert_eq!(second.status(), SoundStatus::Playing);
+        assert_eq!(sink.current_token(), Some(second.clone()));
+
+        for _ in 0..10 {
+            queue_rx.next();
+        }
+        assert_eq!(second.wait_until_end(), SoundStatus::Finished);
+        assert_eq!(sink.current_token(), None);
+    }
+
+    #[test]
+    fn test_cleared_tokens_are_cancelled() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        let first = sink.append(SamplesBuffer::new(1, 1000, vec![1i16; 100]));
+        let second = sink.append(SamplesBuffer::new(1, 1000, vec![2i16; 100]));
+        queue_rx.next();
+
+        std::thread::scope(|s| {
+            let waiter = s.spawn(|| second.wait_until_start());
+            s.spawn(|| {
+                for _ in 0..100 {
+                    queue_rx.next();
+                }
+            });
+            sink.clear();
+            assert_eq!(waiter.join().unwrap(), SoundStatus::Cancelled);
+        });
+        assert_eq!(first.wait_until_end(), SoundStatus::Cancelled);
+        assert_eq!(second.wait_until_end(), SoundStatus::Cancelled);
+        assert_eq!(sink.current_token(), None);
+    }
+
+    #[test]
+    fn test_rejects_sounds_without_channels() {
+        use crate::source::{FormatError, Zero};
+
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let result = sink.try_append(Zero::<f32>::new_samples(0, 48000, 10));
+        assert_eq!(result.unwrap_err(), FormatError::ZeroChannels);
+
+        let token = sink.append(Zero::<f32>::new_samples(2, 0, 10));
+        assert_eq!(token.status(), SoundStatus::Cancelled);
+        assert!(sink.empty());
+        assert_eq!(queue_rx.next(), Some(0.0));
+    }
+
+    #[test]
+    fn test_token_of_dropped_output_is_cancelled() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+
+        let token = sink.append(SamplesBuffer::new(1, 1000, vec![10i16; 100]));
+        queue_rx.next();
+        drop(queue_rx);
+        assert_eq!(token.wait_until_end(), SoundStatus::Cancelled);
+    }
 }
@@ -1,5 +1,8 @@
 use std::time::Duration;
 
+use cpal::Sample as CpalSample;
+
+use crate::source::SmoothedParam;
 use crate::{Sample, Source};
 
 use super::SeekError;
@@ -10,21 +13,66 @@ where
     I: Source,
     I::Item: Sample,
 {
-    Amplify { input, factor }
+    Amplify {
+        input,
+        factor: SmoothedParam::linear(factor, Duration::ZERO, 1),
+        clipping: None,
+    }
+}
+
+/// Internal function that builds a `Amplify` object that keeps its output within full scale.
+pub fn amplify_clamped<I>(input: I, factor: f32, clipping: Clipping) -> Amplify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Amplify {
+        input,
+        factor: SmoothedParam::linear(factor, Duration::ZERO, 1),
+        clipping: Some(clipping),
+    }
+}
+
+/// How [`Source::amplify_clamped`] keeps amplified samples within full scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clipping {
+    /// Cuts off everything beyond full scale. Quiet parts are untouched, but loud peaks get
+    /// flat tops that sound harsh.
+    Clamp,
+    /// Bends the signal towards full scale with `tanh`, which gives a much warmer distortion
+    /// than clamping. This also slightly compresses samples well below full scale.
+    Saturate,
 }
 
 /// Filter that modifies each sample by a given value.
 #[derive(Clone, Debug)]
 pub struct Amplify<I> {
     input: I,
-    factor: f32,
+    // Advances one step per sample while gliding.
+    factor: SmoothedParam,
+    clipping: Option<Clipping>,
 }
 
 impl<I> Amplify<I> {
     /// Modifies the amplification factor.
+    ///
+    /// Stops a glide started by [`set_factor_smooth`](Amplify::set_factor_smooth).
     #[inline]
     pub fn set_factor(&mut self, factor: f32) {
-        self.factor = factor;
+        self.factor.set_value(factor);
+    }
+
+    /// Returns the current amplification factor.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor.value()
+    }
+
+    /// Changes how the amplified samples are kept within full scale, `None` lets them
+    /// exceed it.
+    #[inline]
+    pub fn set_clipping(&mut self, clipping: Option<Clipping>) {
+        self.clipping = clipping;
     }
 
     /// Returns a reference to the inner source.
@@ -46,6 +94,42 @@ impl<I> Amplify<I> {
     }
 }
 
+impl<I> Amplify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Glides the amplification factor linearly from its current value to `target` over
+    /// `duration`, changing it every sample so the change does not click.
+    #[inline]
+    pub fn set_factor_smooth(&mut self, target: f32, duration: Duration) {
+        let samples_per_sec = self
+            .input
+            .sample_rate()
+            .saturating_mul(self.input.channels() as u32);
+        self.factor.set_value(self.factor.value());
+        self.factor.set_update_rate(samples_per_sec);
+        self.factor.set_time(duration);
+        self.factor.set_target(target);
+    }
+
+    #[inline]
+    fn amplify_sample(&mut self, value: I::Item) -> I::Item {
+        let factor = self.factor.next_value();
+        let Some(clipping) = self.clipping else {
+            return value.amplify(factor);
+        };
+
+        let amplified = value.to_float_sample().to_sample::<f32>() * factor;
+        let clipped = match clipping {
+            Clipping::Clamp => amplified.clamp(-1.0, 1.0),
+            Clipping::Saturate => amplified.tanh(),
+        };
+        let float: <I::Item as CpalSample>::Float = clipped.to_sample();
+        float.to_sample()
+    }
+}
+
 impl<I> Iterator for Amplify<I>
 where
     I: Source,
@@ -55,7 +139,8 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
-        self.input.next().map(|value| value.amplify(self.factor))
+        let value = self.input.next()?;
+        Some(self.amplify_sample(value))
     }
 
     #[inline]
@@ -96,8 +181,105 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [I::Item]) -> usize {
+        let filled = self.input.fill_buffer(out);
+        for sample in &mut out[..filled] {
+            *sample = self.amplify_sample(*sample);
+        }
+        filled
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{Clipping, SineWave, Source};
+
+    #[test]
+    fn clamp_cuts_off_at_full_scale() {
+        let source = SamplesBuffer::new(1, 44100, vec![0.1f32, 0.5, -0.5, -1.0]);
+        let samples: Vec<f32> = source.amplify_clamped(4.0, Clipping::Clamp).collect();
+        assert_eq!(samples, vec![0.4, 1.0, -1.0, -1.0]);
+
+        let source = SamplesBuffer::new(1, 44100, vec![i16::MAX / 2, i16::MIN]);
+        let samples: Vec<i16> = source.amplify_clamped(3.0, Clipping::Clamp).collect();
+        assert_eq!(samples, vec![i16::MAX, i16::MIN]);
+    }
+
+    #[test]
+    fn saturated_sine_stays_within_full_scale_and_smooth() {
+        let input: Vec<f32> = SineWave::new(440.0).take(4800).collect();
+        let output: Vec<f32> = SineWave::new(440.0)
+            .amplify_clamped(4.0, Clipping::Saturate)
+            .take(4800)
+            .collect();
+
+        assert!(output.iter().all(|s| s.abs() < 1.0));
+        // the peaks are rounded off instead of cut flat
+        assert!(output.windows(2).all(|pair| pair[0] != pair[1]));
+        // tanh never makes the signal change faster than the amplified input does
+        let max_step = |samples: &[f32]| {
+            samples
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .fold(0.0f32, f32::max)
+        };
+        assert!(max_step(&output) <= 4.0 * max_step(&input) + 1e-6);
+    }
+
+    #[test]
+    fn set_factor_smooth_glides_every_sample() {
+        let mut source = SamplesBuffer::new(2, 1000, vec![1.0f32; 16]).amplify(1.0);
+        // 2 channels at 1000 Hz, 8 samples
+        source.set_factor_smooth(0.2, Duration::from_millis(4));
+        let samples: Vec<f32> = source.by_ref().take(8).collect();
+        assert!(samples.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!((samples[0] - 0.9).abs() < 1e-6);
+        assert!((source.factor() - 0.2).abs() < 1e-6);
+        assert!(source.all(|sample| (sample - 0.2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn fill_buffer_amplifies() {
+        let mut source = SamplesBuffer::new(1, 44100, vec![0.1f32, 0.5, -0.5, -1.0])
+            .amplify_clamped(4.0, Clipping::Clamp);
+        let mut out = [0.0; 3];
+        assert_eq!(source.fill_buffer(&mut out), 3);
+        assert_eq!(out, [0.4, 1.0, -1.0]);
+        assert_eq!(source.fill_buffer(&mut out), 1);
+        assert_eq!(out[0], -1.0);
+    }
 }
@@ -177,10 +177,35 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -15,12 +15,18 @@ where
     I::Item: Sample,
 {
     let total_duration = input.total_duration();
+    let total_len = input.total_len();
+    let latency_samples = input.latency_samples();
+    let label = input.label().map(Arc::from);
     let first_frame = extract(input);
 
     Buffered {
         current_frame: first_frame,
         position_in_frame: 0,
         total_duration,
+        total_len,
+        latency_samples,
+        label,
     }
 }
 
@@ -38,6 +44,15 @@ where
 
     /// Obtained once at creation and never modified again.
     total_duration: Option<Duration>,
+
+    /// Obtained once at creation and never modified again.
+    total_len: Option<u64>,
+
+    /// Obtained once at creation and never modified again.
+    latency_samples: usize,
+
+    /// Obtained once at creation and never modified again.
+    label: Option<Arc<str>>,
 }
 
 enum Frame<I>
@@ -65,6 +80,8 @@ where
     data: Vec<I::Item>,
     channels: u16,
     rate: u32,
+    // The size hint of the input after this frame was extracted.
+    after: (usize, Option<usize>),
     next: Mutex<Arc<Frame<I>>>,
 }
 
@@ -125,6 +142,7 @@ where
         data,
         channels,
         rate,
+        after: input.size_hint(),
         next: Mutex::new(Arc::new(Frame::Input(Mutex::new(Some(input))))),
     }))
 }
@@ -196,14 +214,26 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        // TODO:
-        (0, None)
+        match &*self.current_frame {
+            Frame::Data(FrameData { data, after, .. }) => {
+                let left = data.len() - self.position_in_frame;
+                (
+                    after.0.saturating_add(left),
+                    after.1.and_then(|max| max.checked_add(left)),
+                )
+            }
+            Frame::End => (0, Some(0)),
+            Frame::Input(_) => unreachable!(),
+        }
     }
 }
 
-// TODO: uncomment when `size_hint` is fixed
-/*impl<I> ExactSizeIterator for Amplify<I> where I: Source + ExactSizeIterator, I::Item: Sample {
-}*/
+impl<I> ExactSizeIterator for Buffered<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
 
 impl<I> Source for Buffered<I>
 where
@@ -242,6 +272,11 @@ where
         self.total_duration
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.total_len
+    }
+
     /// Can not support seek, in the end state we lose the underlying source
     /// which makes seeking back impossible.
     #[inline]
@@ -250,6 +285,16 @@ where
             underlying_source: std::any::type_name::<Self>(),
         })
     }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.latency_samples
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
 }
 
 impl<I> Clone for Buffered<I>
@@ -263,6 +308,39 @@ where
             current_frame: self.current_frame.clone(),
             position_in_frame: self.position_in_frame,
             total_duration: self.total_duration,
+            total_len: self.total_len,
+            latency_samples: self.latency_samples,
+            label: self.label.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn len_counts_down_over_frames() {
+        // longer than one frame of the buffer
+        let source = SamplesBuffer::new(1, 48000, vec![1i16; 40000]).buffered();
+        let mut copy = source.clone();
+        assert_eq!(source.len(), 40000);
+
+        for left in (0..40000).rev() {
+            copy.next();
+            assert_eq!(copy.len(), left);
+        }
+        assert_eq!(copy.next(), None);
+        // the clone kept its own position
+        assert_eq!(source.len(), 40000);
+    }
+
+    #[test]
+    fn keeps_the_exact_length() {
+        // 3 samples at 7Hz do not make a whole number of nanoseconds
+        let source = SamplesBuffer::new(1, 7, vec![1i16; 3]).buffered();
+        assert_eq!(source.total_len(), Some(3));
+        assert_eq!(source.clone().total_len(), Some(3));
+    }
+}
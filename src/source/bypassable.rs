@@ -0,0 +1,279 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::source::{SmoothedParam, UniformSourceIterator};
+use crate::{Sample, Source};
+
+use super::{Buffered, SeekError};
+
+/// Switching between the effect and the input is spread over this duration to avoid clicks.
+const BYPASS_FADE: Duration = Duration::from_millis(5);
+
+/// Internal function that builds a `Bypassable` object.
+pub fn bypassable<I, E, F>(input: I, effect: F) -> Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+    E: Source<Item = I::Item>,
+    F: FnOnce(Buffered<I>) -> E,
+{
+    let input = input.buffered();
+    let wet = effect(input.clone());
+    let channels = wet.channels();
+    let sample_rate = wet.sample_rate();
+    let dry = UniformSourceIterator::new(input, channels, sample_rate);
+    // delay the input by the latency of the effect so both stay in phase
+    let dry_delay = wet.latency_samples().saturating_sub(dry.latency_samples()) * channels as usize;
+
+    Bypassable {
+        dry,
+        wet,
+        dry_delay,
+        bypassed: Arc::new(AtomicBool::new(false)),
+        applied: false,
+        mix: SmoothedParam::linear(0.0, BYPASS_FADE, sample_rate * channels as u32),
+    }
+}
+
+/// An effect that can be bypassed while it plays, see [`Source::bypassable`].
+///
+/// While bypassed the input plays unchanged, delayed by the
+/// [`latency_samples`](Source::latency_samples) of the effect so both stay in phase. Switching
+/// crossfades between the two over a few milliseconds. The effect keeps processing the input
+/// while it is bypassed, so switching back continues where a never bypassed effect would be,
+/// a reverb tail included.
+///
+/// The input is [`buffered`](Source::buffered) to feed both the effect and the bypass. The
+/// effect has to read its input at the rate it plays, effects that change the speed can not
+/// be bypassed.
+pub struct Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    dry: UniformSourceIterator<Buffered<I>, I::Item>,
+    wet: E,
+    // Silent samples left to play before the input, to compensate for the latency of the effect.
+    dry_delay: usize,
+    bypassed: Arc<AtomicBool>,
+    // The value of `bypassed` the crossfade is heading to.
+    applied: bool,
+    // How much of the input is heard, from `0.0` for only the effect to `1.0` for only the
+    // input.
+    mix: SmoothedParam,
+}
+
+impl<I, E> Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Bypasses the effect if `bypassed` is true, plays it again otherwise.
+    #[inline]
+    pub fn bypass(&self, bypassed: bool) {
+        self.bypassed.store(bypassed, Ordering::Relaxed);
+    }
+
+    /// Returns true if the effect is bypassed.
+    #[inline]
+    pub fn is_bypassed(&self) -> bool {
+        self.bypassed.load(Ordering::Relaxed)
+    }
+
+    /// Returns the flag that bypasses the effect while it is true. Keep it to switch the
+    /// effect from another thread once the source has been added to a sink or mixer.
+    #[inline]
+    pub fn bypass_handle(&self) -> Arc<AtomicBool> {
+        self.bypassed.clone()
+    }
+
+    /// Returns a reference to the effect.
+    #[inline]
+    pub fn inner(&self) -> &E {
+        &self.wet
+    }
+
+    /// Returns a mutable reference to the effect.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.wet
+    }
+
+    /// Returns the effect.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        self.wet
+    }
+}
+
+impl<I, E> Iterator for Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+    E: Source<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let wet = self.wet.next()?;
+        let dry = if self.dry_delay > 0 {
+            self.dry_delay -= 1;
+            I::Item::zero_value()
+        } else {
+            self.dry.next().unwrap_or_else(I::Item::zero_value)
+        };
+
+        let bypassed = self.bypassed.load(Ordering::Relaxed);
+        if bypassed != self.applied {
+            self.applied = bypassed;
+            self.mix.set_target(if bypassed { 1.0 } else { 0.0 });
+        }
+        if !self.mix.is_smoothing() {
+            return Some(if self.applied { dry } else { wet });
+        }
+        let mix = self.mix.next_value();
+        Some(wet.amplify(1.0 - mix).saturating_add(dry.amplify(mix)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.wet.size_hint()
+    }
+}
+
+impl<I, E> ExactSizeIterator for Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+    E: Source<Item = I::Item> + ExactSizeIterator,
+{
+}
+
+impl<I, E> Source for Bypassable<I, E>
+where
+    I: Source,
+    I::Item: Sample,
+    E: Source<Item = I::Item>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.wet.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.wet.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.wet.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.wet.total_duration()
+    }
+
+    /// Can not support seek, the input is buffered which can not seek either.
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.wet.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.dry.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn bypass_crossfades_to_the_input() {
+        let input = SamplesBuffer::new(1, 1000, vec![1.0f32; 40]);
+        let mut source = input.bypassable(|input| input.amplify(0.5));
+        assert!(!source.is_bypassed());
+        assert_eq!(source.next(), Some(0.5));
+
+        source.bypass(true);
+        let fade: Vec<f32> = source.by_ref().take(5).collect();
+        assert!(fade.windows(2).all(|w| w[1] > w[0]));
+        assert!(fade.iter().all(|&sample| (0.5..=1.0).contains(&sample)));
+        assert_eq!(source.next(), Some(1.0));
+
+        let handle = source.bypass_handle();
+        handle.store(false, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(source.by_ref().nth(5), Some(0.5));
+        assert_eq!(source.count(), 27);
+    }
+
+    #[test]
+    fn input_is_delayed_by_the_latency_of_the_effect() {
+        struct Latent<I> {
+            input: I,
+            latency: usize,
+        }
+
+        impl<I: Source<Item = f32>> Iterator for Latent<I> {
+            type Item = f32;
+
+            fn next(&mut self) -> Option<f32> {
+                if self.latency > 0 {
+                    self.latency -= 1;
+                    return Some(0.0);
+                }
+                self.input.next()
+            }
+        }
+
+        impl<I: Source<Item = f32>> Source for Latent<I> {
+            fn current_frame_len(&self) -> Option<usize> {
+                None
+            }
+
+            fn channels(&self) -> u16 {
+                self.input.channels()
+            }
+
+            fn sample_rate(&self) -> u32 {
+                self.input.sample_rate()
+            }
+
+            fn total_duration(&self) -> Option<Duration> {
+                None
+            }
+
+            fn latency_samples(&self) -> usize {
+                2
+            }
+        }
+
+        let input = SamplesBuffer::new(1, 1000, vec![1.0f32, 2.0, 3.0]);
+        let source = input.bypassable(|input| Latent { input, latency: 2 });
+        source.bypass(true);
+        assert_eq!(source.latency_samples(), 2);
+        // the input lines up with the effect, which plays it unchanged after its latency
+        let samples: Vec<f32> = source.collect();
+        let expected = [0.0, 0.0, 1.0, 2.0, 3.0];
+        assert_eq!(samples.len(), expected.len());
+        assert!(samples
+            .iter()
+            .zip(expected)
+            .all(|(sample, expected)| (sample - expected).abs() < 1e-6));
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+// Resolution of the fractional part of a delay, in parts of a sample.
+const FRACTION_STEPS: u32 = 1 << 16;
+
+/// Internal function that builds a `ChannelDelay` object.
+pub fn channel_delay<I>(input: I, delays: &[Duration]) -> ChannelDelay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(
+        delays.len(),
+        input.channels() as usize,
+        "delay_channels needs exactly one delay per channel"
+    );
+
+    let requested_max = delays.iter().max().copied().unwrap_or_default();
+    let sample_rate = input.sample_rate() as f64;
+    let delays: Vec<_> = delays
+        .iter()
+        .map(|delay| {
+            let frames = delay.as_secs_f64() * sample_rate;
+            let whole = frames.floor();
+            let fraction = ((frames - whole) * FRACTION_STEPS as f64).round() as u32;
+            // rounding can push the fraction up to a whole sample
+            if fraction == FRACTION_STEPS {
+                (whole as usize + 1, 0)
+            } else {
+                (whole as usize, fraction)
+            }
+        })
+        .collect();
+    let max_delay = delays
+        .iter()
+        .map(|&(whole, fraction)| whole + (fraction > 0) as usize)
+        .max()
+        .unwrap_or(0);
+
+    let mut source = ChannelDelay {
+        history: Vec::new(),
+        frame: Vec::with_capacity(delays.len()),
+        frame_offset: 0,
+        tail_frames: max_delay,
+        max_delay,
+        requested_max,
+        delays,
+        input,
+    };
+    source.reset_history();
+    source
+}
+
+/// Delays each channel of the input by its own, possibly fractional, number of samples.
+///
+/// Fractional delays are linearly interpolated. The source plays until the channel with the
+/// longest delay has played all of its samples.
+#[derive(Clone, Debug)]
+pub struct ChannelDelay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Per channel the whole number of samples to delay by and the fraction of a sample
+    // in `FRACTION_STEPS`.
+    delays: Vec<(usize, u32)>,
+    // Per channel the most recent input samples, the newest at the back.
+    history: Vec<VecDeque<I::Item>>,
+    frame: Vec<I::Item>,
+    frame_offset: usize,
+    // Frames left to play once the input has run out.
+    tail_frames: usize,
+    max_delay: usize,
+    requested_max: Duration,
+}
+
+impl<I> ChannelDelay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn reset_history(&mut self) {
+        self.history = self
+            .delays
+            .iter()
+            .map(|&(whole, _)| VecDeque::from(vec![I::Item::zero_value(); whole + 2]))
+            .collect();
+        self.frame.clear();
+        self.frame_offset = 0;
+        self.tail_frames = self.max_delay;
+    }
+
+    /// Reads the next frame of the input into the history and computes the delayed frame.
+    fn next_frame(&mut self) -> bool {
+        self.frame.clear();
+        self.frame_offset = 0;
+
+        let mut input_ended = false;
+        for channel in 0..self.delays.len() {
+            let sample = if input_ended { None } else { self.input.next() };
+            if sample.is_none() && channel == 0 {
+                if self.tail_frames == 0 {
+                    return false;
+                }
+                self.tail_frames -= 1;
+                input_ended = true;
+            }
+
+            let history = &mut self.history[channel];
+            history.pop_front();
+            history.push_back(sample.unwrap_or_else(I::Item::zero_value));
+
+            let (whole, fraction) = self.delays[channel];
+            let newest = history.len() - 1;
+            let delayed = history[newest - whole];
+            let before = history[newest - whole - 1];
+            self.frame
+                .push(Sample::lerp(delayed, before, fraction, FRACTION_STEPS));
+        }
+        true
+    }
+}
+
+impl<I> Iterator for ChannelDelay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.frame_offset >= self.frame.len() && !self.next_frame() {
+            return None;
+        }
+        let sample = self.frame[self.frame_offset];
+        self.frame_offset += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.input.size_hint();
+        let buffered = self.frame.len() - self.frame_offset;
+        let tail = self.tail_frames * self.delays.len();
+        (
+            min.saturating_add(buffered + tail),
+            max.and_then(|max| max.checked_add(buffered + tail)),
+        )
+    }
+}
+
+impl<I> Source for ChannelDelay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input
+            .total_duration()
+            .map(|duration| duration + self.requested_max)
+    }
+
+    /// Seeks the input. The delayed channels start from silence again after the seek, as
+    /// if playback began at `pos`.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.input.try_seek_exact(pos)?;
+        self.reset_history();
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn whole_sample_delays() {
+        let inner = SamplesBuffer::new(2, 1000, vec![1.0f32, 10.0, 2.0, 20.0, 3.0, 30.0]);
+        let source = inner.delay_channels(&[Duration::ZERO, Duration::from_millis(2)]);
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            vec![1.0, 0.0, 2.0, 0.0, 3.0, 10.0, 0.0, 20.0, 0.0, 30.0]
+        );
+    }
+
+    #[test]
+    fn fractional_delay_interpolates() {
+        let inner = SamplesBuffer::new(1, 1000, vec![4.0f32, 8.0]);
+        let source = inner.delay_channels(&[Duration::from_micros(500)]);
+        assert_eq!(source.total_duration(), Some(Duration::from_micros(2500)));
+        assert_eq!(source.collect::<Vec<_>>(), vec![2.0, 6.0, 4.0]);
+    }
+
+    #[test]
+    fn no_delay_is_unchanged() {
+        let v = vec![1i16, -1, 2, -2, 3, -3];
+        let inner = SamplesBuffer::new(2, 44100, v.clone());
+        let source = inner.delay_channels(&[Duration::ZERO; 2]);
+        assert_eq!(source.collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_on_wrong_number_of_delays() {
+        let inner = SamplesBuffer::new(2, 44100, vec![0i16; 4]);
+        inner.delay_channels(&[Duration::ZERO]);
+    }
+}
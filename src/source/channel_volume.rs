@@ -6,6 +6,14 @@ use super::SeekError;
 
 /// Combines channels in input into a single mono source, then plays that mono sound
 /// to each channel at the volume given for that channel.
+///
+/// This is the building block of [`Spatial`](crate::source::Spatial) and
+/// [`Pan`](crate::source::Pan), and can be used for custom panners: the number of output
+/// channels is the number of volumes, independent of the channels of the input.
+///
+/// The input channels of a frame are summed, not averaged. A stereo input with both channels
+/// at `0.5` plays `1.0` on every output channel with a volume of `1.0`. Divide the volumes by
+/// the number of input channels to play the average instead.
 #[derive(Clone, Debug)]
 pub struct ChannelVolume<I>
 where
@@ -28,11 +36,21 @@ where
     /// Wrap the input source and make it mono. Play that mono sound to each
     /// channel at the volume set by the user. The volume can be changed using
     /// [`ChannelVolume::set_volume`].
+    ///
+    /// There is one output channel per entry in `channel_volumes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel_volumes` is empty.
     pub fn new(mut input: I, channel_volumes: Vec<f32>) -> ChannelVolume<I>
     where
         I: Source,
         I::Item: Sample,
     {
+        assert!(
+            !channel_volumes.is_empty(),
+            "ChannelVolume needs at least one output channel"
+        );
         let mut sample = None;
         for _ in 0..input.channels() {
             if let Some(s) = input.next() {
@@ -51,16 +69,29 @@ where
         }
     }
 
-    /// Sets the volume for a given channel number. Will panic if channel number
-    /// is invalid.
-    pub fn set_volume(&mut self, channel: usize, volume: f32) {
-        self.channel_volumes[channel] = volume;
+    /// Sets the volume for a given output channel, starting at `0`. The new volume is used
+    /// from the next sample of that channel onwards.
+    ///
+    /// Returns false, and changes nothing, if there is no such channel.
+    pub fn set_volume(&mut self, channel: usize, volume: f32) -> bool {
+        match self.channel_volumes.get_mut(channel) {
+            Some(current) => {
+                *current = volume;
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Gets the volume for a given channel number. Will panic if channel number
-    /// is invalid.
-    pub fn get_volume(&self, channel: usize) -> f32 {
-        self.channel_volumes[channel]
+    /// Gets the volume for a given output channel, or `None` if there is no such channel.
+    pub fn get_volume(&self, channel: usize) -> Option<f32> {
+        self.channel_volumes.get(channel).copied()
+    }
+
+    /// Returns the volumes of all output channels.
+    #[inline]
+    pub fn volumes(&self) -> &[f32] {
+        &self.channel_volumes
     }
 
     /// Returns a reference to the inner source.
@@ -153,4 +184,73 @@ where
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{ChannelVolume, Source};
+
+    #[test]
+    fn mono_output() {
+        let input = SamplesBuffer::new(2, 48000, vec![1.0f32, 2.0, 3.0, 4.0]);
+        let source = ChannelVolume::new(input, vec![0.5]);
+        assert_eq!(source.channels(), 1);
+        assert_eq!(source.collect::<Vec<_>>(), vec![1.5, 3.5]);
+    }
+
+    #[test]
+    fn stereo_output() {
+        let input = SamplesBuffer::new(1, 48000, vec![1.0f32, 2.0]);
+        let mut source = ChannelVolume::new(input, vec![1.0, 0.25]);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.next(), Some(1.0));
+        assert_eq!(source.next(), Some(0.25));
+
+        assert!(source.set_volume(0, 0.0));
+        assert_eq!(source.get_volume(0), Some(0.0));
+        assert_eq!(source.collect::<Vec<_>>(), vec![0.0, 0.5]);
+    }
+
+    #[test]
+    fn surround_output() {
+        let input = SamplesBuffer::new(2, 48000, vec![0.25f32, 0.75]);
+        let volumes = vec![1.0, 1.0, 0.5, 0.0, 0.0, 2.0];
+        let source = ChannelVolume::new(input, volumes.clone());
+        assert_eq!(source.channels(), 6);
+        assert_eq!(source.volumes(), &volumes[..]);
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            vec![1.0, 1.0, 0.5, 0.0, 0.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn invalid_channel_is_ignored() {
+        let input = SamplesBuffer::new(1, 48000, vec![1.0f32]);
+        let mut source = ChannelVolume::new(input, vec![1.0, 1.0]);
+        assert!(!source.set_volume(2, 0.0));
+        assert_eq!(source.get_volume(2), None);
+        assert_eq!(source.volumes(), &[1.0, 1.0]);
+    }
 }
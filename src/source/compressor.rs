@@ -0,0 +1,566 @@
+use std::time::Duration;
+
+use cpal::{FromSample, Sample as CpalSample};
+
+use crate::source::UniformSourceIterator;
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Compressor` object.
+pub fn compressor<I>(input: I, settings: CompressorSettings) -> Compressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Compressor {
+        input,
+        gain: GainComputer::new(settings),
+    }
+}
+
+/// Internal function that builds a `SidechainCompressor` object.
+pub fn compressor_sidechain<I, K>(
+    input: I,
+    settings: CompressorSettings,
+    key: K,
+) -> SidechainCompressor<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+    f32: FromSample<K::Item>,
+{
+    let key = UniformSourceIterator::new(key, input.channels(), input.sample_rate());
+    SidechainCompressor {
+        input,
+        key,
+        gain: GainComputer::new(settings),
+    }
+}
+
+/// Settings of a [`Compressor`] or [`SidechainCompressor`].
+///
+/// Levels are in dB relative to full scale, so `0.0` is full scale and `-6.0` about half
+/// of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompressorSettings {
+    /// Level above which the gain is reduced.
+    pub threshold: f32,
+    /// How strongly levels above the threshold are reduced. With a ratio of 4 a level 8 dB
+    /// over the threshold comes out 2 dB over it. Ratios below 1 are treated as 1, which
+    /// leaves the signal as is.
+    pub ratio: f32,
+    /// How quickly the gain goes down once the level rises above the threshold.
+    pub attack: Duration,
+    /// How quickly the gain recovers once the level falls below the threshold again.
+    pub release: Duration,
+    /// Width in dB of the range around the threshold in which the compression gradually
+    /// sets in. Zero gives a hard knee.
+    pub knee: f32,
+    /// Gain in dB applied after compressing, to make up for the lost level.
+    pub makeup_gain: f32,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        CompressorSettings {
+            threshold: -20.0,
+            ratio: 4.0,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(100),
+            knee: 6.0,
+            makeup_gain: 0.0,
+        }
+    }
+}
+
+/// Follows the level of the detector signal and computes the gain from it.
+#[derive(Clone, Debug)]
+struct GainComputer {
+    settings: CompressorSettings,
+    // The smoothed gain reduction in dB, zero or less.
+    reduction: f32,
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    // The samples per second the coefficients were computed for.
+    rate: u32,
+}
+
+impl GainComputer {
+    fn new(settings: CompressorSettings) -> Self {
+        GainComputer {
+            settings,
+            reduction: 0.0,
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            rate: 0,
+        }
+    }
+
+    fn set_settings(&mut self, settings: CompressorSettings) {
+        self.settings = settings;
+        // recompute the coefficients on the next sample
+        self.rate = 0;
+    }
+
+    /// Takes the next sample of the detector signal and returns the gain for the next
+    /// sample of the output.
+    #[inline]
+    fn process(&mut self, detector: f32, samples_per_sec: u32) -> f32 {
+        if samples_per_sec != self.rate {
+            self.rate = samples_per_sec;
+            self.attack_coefficient = coefficient(self.settings.attack, samples_per_sec);
+            self.release_coefficient = coefficient(self.settings.release, samples_per_sec);
+        }
+
+        let level = 20.0 * detector.abs().max(1e-9).log10();
+        let target = self.curve(level) - level;
+        let coefficient = if target < self.reduction {
+            self.attack_coefficient
+        } else {
+            self.release_coefficient
+        };
+        self.reduction = target + coefficient * (self.reduction - target);
+
+        db_to_gain(self.reduction + self.settings.makeup_gain)
+    }
+
+    /// The output level for a steady input `level`, both in dB.
+    fn curve(&self, level: f32) -> f32 {
+        let CompressorSettings {
+            threshold, knee, ..
+        } = self.settings;
+        let ratio = self.settings.ratio.max(1.0);
+        let over = level - threshold;
+
+        if knee > 0.0 && 2.0 * over.abs() <= knee {
+            let into_knee = over + knee / 2.0;
+            level + (1.0 / ratio - 1.0) * into_knee * into_knee / (2.0 * knee)
+        } else if over <= 0.0 {
+            level
+        } else {
+            threshold + over / ratio
+        }
+    }
+}
+
+/// Coefficient of a one pole filter that covers about 63% of a change in `time`.
+fn coefficient(time: Duration, samples_per_sec: u32) -> f32 {
+    let samples = time.as_secs_f32() * samples_per_sec as f32;
+    if samples > 0.0 {
+        (-1.0 / samples).exp()
+    } else {
+        0.0
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Samples per second of all channels together.
+fn samples_per_sec<I: Source>(input: &I) -> u32
+where
+    I::Item: Sample,
+{
+    input.sample_rate() * input.channels() as u32
+}
+
+/// A feed-forward dynamic range compressor, lowering the volume while the source is loud.
+///
+/// The level is detected from the peaks of all channels together, so every channel gets
+/// the same gain and the stereo image stays in place.
+#[derive(Clone, Debug)]
+pub struct Compressor<I> {
+    input: I,
+    gain: GainComputer,
+}
+
+impl<I> Compressor<I> {
+    /// Returns the settings of the compressor.
+    #[inline]
+    pub fn settings(&self) -> CompressorSettings {
+        self.gain.settings
+    }
+
+    /// Changes the settings of the compressor, the current gain reduction glides to the
+    /// one of the new settings.
+    #[inline]
+    pub fn set_settings(&mut self, settings: CompressorSettings) {
+        self.gain.set_settings(settings);
+    }
+
+    /// Returns by how many dB the volume is currently lowered, not counting the makeup gain.
+    /// Zero or negative.
+    #[inline]
+    pub fn gain_reduction(&self) -> f32 {
+        self.gain.reduction
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Compressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        let detector = sample.to_float_sample().to_sample::<f32>();
+        let gain = self.gain.process(detector, samples_per_sec(&self.input));
+        Some(sample.amplify(gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Compressor<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Compressor<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+/// A compressor that lowers the volume of its input while another source, the key, is
+/// loud. For example music that ducks under a voice.
+///
+/// The key is converted to the channel count and sample rate the input had when the
+/// compressor was built and is read alongside it, sample for sample. Once the key ends the
+/// gain recovers and the input plays on unchanged.
+#[derive(Clone)]
+pub struct SidechainCompressor<I, K>
+where
+    K: Source,
+    K::Item: Sample,
+{
+    input: I,
+    key: UniformSourceIterator<K, f32>,
+    gain: GainComputer,
+}
+
+impl<I, K> SidechainCompressor<I, K>
+where
+    K: Source,
+    K::Item: Sample,
+{
+    /// Returns the settings of the compressor.
+    #[inline]
+    pub fn settings(&self) -> CompressorSettings {
+        self.gain.settings
+    }
+
+    /// Changes the settings of the compressor, the current gain reduction glides to the
+    /// one of the new settings.
+    #[inline]
+    pub fn set_settings(&mut self, settings: CompressorSettings) {
+        self.gain.set_settings(settings);
+    }
+
+    /// Returns by how many dB the volume is currently lowered, not counting the makeup gain.
+    /// Zero or negative.
+    #[inline]
+    pub fn gain_reduction(&self) -> f32 {
+        self.gain.reduction
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, K> Iterator for SidechainCompressor<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+    f32: FromSample<K::Item>,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        let detector = self.key.next().unwrap_or(0.0);
+        let gain = self.gain.process(detector, samples_per_sec(&self.input));
+        Some(sample.amplify(gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, K> ExactSizeIterator for SidechainCompressor<I, K>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+    f32: FromSample<K::Item>,
+{
+}
+
+impl<I, K> Source for SidechainCompressor<I, K>
+where
+    I: Source,
+    I::Item: Sample,
+    K: Source,
+    K::Item: Sample,
+    f32: FromSample<K::Item>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    /// Seeks the key as well, so both stay aligned.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.key.try_seek(pos)?;
+        self.input.try_seek(pos)
+    }
+
+    /// Seeks the key to where the input landed, so both stay aligned.
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.key.try_seek(pos)?;
+        let reached = self.input.try_seek_exact(pos)?;
+        if reached != pos {
+            self.key.try_seek(reached)?;
+        }
+        Ok(reached)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.key.is_seekable() && self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{CompressorSettings, Source};
+
+    const RATE: u32 = 1000;
+
+    fn constant(value: f32, samples: usize) -> SamplesBuffer<f32> {
+        SamplesBuffer::new(1, RATE, vec![value; samples])
+    }
+
+    fn hard_knee() -> CompressorSettings {
+        CompressorSettings {
+            knee: 0.0,
+            ..CompressorSettings::default()
+        }
+    }
+
+    fn db(value: f32) -> f32 {
+        20.0 * value.abs().log10()
+    }
+
+    #[test]
+    fn quiet_signal_is_untouched() {
+        let output: Vec<f32> = constant(0.01, 100).compressor(hard_knee()).collect();
+        assert_eq!(output, vec![0.01; 100]);
+    }
+
+    #[test]
+    fn loud_signal_is_reduced_by_the_ratio() {
+        // 0.5 is about 14 dB over the threshold, a ratio of 4 leaves 3.5 dB of that
+        let mut source = constant(0.5, 2000).compressor(hard_knee());
+        let last = source.by_ref().last().unwrap();
+        let expected = -20.0 + (db(0.5) + 20.0) / 4.0;
+        assert!((db(last) - expected).abs() < 0.01, "{}", db(last));
+        assert!((source.gain_reduction() - (expected - db(0.5))).abs() < 0.01);
+    }
+
+    #[test]
+    fn attack_and_release_take_time() {
+        let mut samples = vec![0.5f32; 10];
+        samples.extend(vec![0.01; 1000]);
+        let output: Vec<f32> = SamplesBuffer::new(1, RATE, samples)
+            .compressor(hard_knee())
+            .collect();
+
+        // with an attack of 10 samples the gain is only partly reduced at first
+        assert!(output[0] > output[9]);
+        assert!(db(output[9]) > -16.5);
+        // and recovers over the release afterwards
+        assert!(output[10] < 0.01);
+        assert!(output[10] < output[100]);
+        assert!((output[1009] - 0.01).abs() < 1e-4);
+    }
+
+    #[test]
+    fn soft_knee_is_continuous() {
+        let settings = CompressorSettings {
+            attack: Duration::ZERO,
+            release: Duration::ZERO,
+            ..CompressorSettings::default()
+        };
+        let levels: Vec<f32> = (0..=100).map(|i| -26.0 + i as f32 * 0.12).collect();
+        let output: Vec<f32> = levels
+            .iter()
+            .map(|level| {
+                let sample = 10f32.powf(level / 20.0);
+                db(constant(sample, 1).compressor(settings).next().unwrap())
+            })
+            .collect();
+        for pair in output.windows(2) {
+            assert!(pair[1] > pair[0]);
+            assert!(pair[1] - pair[0] < 0.13);
+        }
+        // below the knee nothing changes, above it the full ratio applies
+        assert!((output[0] - levels[0]).abs() < 1e-4);
+        let above = -20.0 + (levels[100] + 20.0) / 4.0;
+        assert!((output[100] - above).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sidechain_ducks_under_the_key() {
+        let mut key = vec![0.0f32; 500];
+        key.extend(vec![0.8; 500]);
+        let key = SamplesBuffer::new(1, RATE, key);
+
+        let output: Vec<f32> = constant(0.1, 1500)
+            .compressor_sidechain(hard_knee(), key)
+            .collect();
+        assert_eq!(output.len(), 1500);
+        // untouched while the key is silent
+        assert!((output[499] - 0.1).abs() < 1e-6);
+        // ducked while the key is loud
+        assert!(output[999] < 0.05);
+        // and back once the key has ended
+        assert!((output[1499] - 0.1).abs() < 2e-3);
+    }
+}
@@ -111,6 +111,12 @@ where
             .map(|val| val + self.requested_duration)
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        let delay = remaining_samples(self.requested_duration, self.sample_rate(), self.channels());
+        self.input.total_len().map(|len| len + delay as u64)
+    }
+
     /// Pos is seen from the perspective of the api user.
     ///
     /// # Example
@@ -135,4 +141,47 @@ where
         let compensated_for_delay = pos.saturating_sub(self.requested_duration);
         self.input.try_seek(compensated_for_delay)
     }
+
+    /// A position within the delay is reached exactly, past it the input tells where it
+    /// landed.
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        if pos < self.requested_duration {
+            self.try_seek(pos)?;
+            return Ok(pos);
+        }
+        let reached = self.input.try_seek_exact(pos - self.requested_duration)?;
+        Ok(reached + self.requested_duration)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn total_duration_includes_delay() {
+        let source = SamplesBuffer::new(1, 1000, vec![1i16; 3]).delay(Duration::from_millis(2));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(5)));
+        assert_eq!(source.total_len(), Some(5));
+        assert_eq!(source.collect::<Vec<_>>(), vec![0, 0, 1, 1, 1]);
+    }
 }
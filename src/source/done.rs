@@ -92,8 +92,43 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [I::Item]) -> usize {
+        let filled = self.input.fill_buffer(out);
+        if !self.signal_sent && filled < out.len() {
+            self.signal.fetch_sub(1, Ordering::Relaxed);
+            self.signal_sent = true;
+        }
+        filled
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
 }
@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use cpal::FromSample;
+
+use crate::{Sample, Source};
+
+use super::{Clipping, CompressorSettings};
+
+/// An effect of an [`EffectChain`]. Each variant holds the arguments of the method of
+/// [`Source`] it maps to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EffectSpec {
+    /// [`Source::amplify`].
+    Amplify(f32),
+    /// [`Source::amplify_clamped`].
+    AmplifyClamped(f32, Clipping),
+    /// [`Source::compressor`].
+    Compressor(CompressorSettings),
+    /// [`Source::delay`].
+    Delay(Duration),
+    /// [`Source::fade_in`].
+    FadeIn(Duration),
+    /// [`Source::fade_out`].
+    FadeOut(Duration),
+    /// [`Source::high_pass`], with the cutoff frequency in Hz.
+    HighPass(u32),
+    /// [`Source::high_pass_with_q`], with the cutoff frequency in Hz and the Q.
+    HighPassWithQ(u32, f32),
+    /// [`Source::low_pass`], with the cutoff frequency in Hz.
+    LowPass(u32),
+    /// [`Source::low_pass_with_q`], with the cutoff frequency in Hz and the Q.
+    LowPassWithQ(u32, f32),
+    /// [`Source::pan`].
+    Pan(f32),
+    /// [`Source::reverb`], with the delay and the amplitude of the echo. The source is
+    /// [`buffered`](Source::buffered) first.
+    Reverb(Duration, f32),
+    /// [`Source::skip_duration`].
+    SkipDuration(Duration),
+    /// [`Source::speed`].
+    Speed(f32),
+    /// [`Source::take_duration`].
+    TakeDuration(Duration),
+}
+
+impl EffectSpec {
+    /// Applies this effect to `source`.
+    pub fn apply(
+        &self,
+        source: Box<dyn Source<Item = f32> + Send>,
+    ) -> Box<dyn Source<Item = f32> + Send> {
+        match *self {
+            EffectSpec::Amplify(value) => Box::new(source.amplify(value)),
+            EffectSpec::AmplifyClamped(value, clipping) => {
+                Box::new(source.amplify_clamped(value, clipping))
+            }
+            EffectSpec::Compressor(settings) => Box::new(source.compressor(settings)),
+            EffectSpec::Delay(duration) => Box::new(source.delay(duration)),
+            EffectSpec::FadeIn(duration) => Box::new(source.fade_in(duration)),
+            EffectSpec::FadeOut(duration) => Box::new(source.fade_out(duration)),
+            EffectSpec::HighPass(freq) => Box::new(source.high_pass(freq)),
+            EffectSpec::HighPassWithQ(freq, q) => Box::new(source.high_pass_with_q(freq, q)),
+            EffectSpec::LowPass(freq) => Box::new(source.low_pass(freq)),
+            EffectSpec::LowPassWithQ(freq, q) => Box::new(source.low_pass_with_q(freq, q)),
+            EffectSpec::Pan(position) => Box::new(source.pan(position)),
+            EffectSpec::Reverb(duration, amplitude) => {
+                Box::new(source.buffered().reverb(duration, amplitude))
+            }
+            EffectSpec::SkipDuration(duration) => Box::new(source.skip_duration(duration)),
+            EffectSpec::Speed(ratio) => Box::new(source.speed(ratio)),
+            EffectSpec::TakeDuration(duration) => Box::new(source.take_duration(duration)),
+        }
+    }
+}
+
+/// A list of effects to apply to a source one after another, built at runtime.
+///
+/// Chaining the methods of [`Source`] gives a new type for every effect, which is hard to
+/// name and can not depend on data. An `EffectChain` instead holds the effects as
+/// [`EffectSpec`] values, so a chain can be built from a configuration file, stored next to
+/// chains with other effects, and applied to any number of sources. The result is a boxed
+/// source.
+///
+/// ```
+/// use rodio::source::{EffectChain, EffectSpec, SineWave, Source};
+///
+/// let muffled = true;
+/// let mut chain = EffectChain::new().with(EffectSpec::Amplify(0.5));
+/// if muffled {
+///     chain.push(EffectSpec::LowPass(800));
+/// }
+/// let source = chain.apply(SineWave::new(440.0));
+/// assert_eq!(source.channels(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EffectChain {
+    effects: Vec<EffectSpec>,
+}
+
+impl EffectChain {
+    /// Builds a chain without effects, it passes sources through unchanged.
+    #[inline]
+    pub fn new() -> EffectChain {
+        EffectChain::default()
+    }
+
+    /// Adds an effect at the end of the chain.
+    #[inline]
+    pub fn with(mut self, effect: EffectSpec) -> EffectChain {
+        self.effects.push(effect);
+        self
+    }
+
+    /// Adds an effect at the end of the chain, see [`with`](EffectChain::with).
+    #[inline]
+    pub fn push(&mut self, effect: EffectSpec) {
+        self.effects.push(effect);
+    }
+
+    /// Returns the effects in the order they are applied.
+    #[inline]
+    pub fn effects(&self) -> &[EffectSpec] {
+        &self.effects
+    }
+
+    /// Converts `source` to `f32` samples and applies every effect of the chain to it, in
+    /// order.
+    pub fn apply<S>(&self, source: S) -> Box<dyn Source<Item = f32> + Send>
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample,
+        f32: FromSample<S::Item>,
+    {
+        let source: Box<dyn Source<Item = f32> + Send> = Box::new(source.convert_samples());
+        self.effects
+            .iter()
+            .fold(source, |source, effect| effect.apply(source))
+    }
+}
+
+impl From<Vec<EffectSpec>> for EffectChain {
+    #[inline]
+    fn from(effects: Vec<EffectSpec>) -> EffectChain {
+        EffectChain { effects }
+    }
+}
+
+impl FromIterator<EffectSpec> for EffectChain {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = EffectSpec>>(iter: T) -> EffectChain {
+        EffectChain {
+            effects: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<EffectSpec> for EffectChain {
+    #[inline]
+    fn extend<T: IntoIterator<Item = EffectSpec>>(&mut self, iter: T) {
+        self.effects.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{EffectChain, EffectSpec, Source};
+
+    #[test]
+    fn applies_effects_in_order() {
+        let chain: EffectChain = vec![
+            EffectSpec::Amplify(0.5),
+            EffectSpec::TakeDuration(Duration::from_millis(2)),
+            EffectSpec::Pan(-1.0),
+        ]
+        .into();
+
+        let source = chain.apply(SamplesBuffer::new(1, 1000, vec![i16::MAX; 10]));
+        assert_eq!(source.channels(), 2);
+        let samples: Vec<f32> = source.collect();
+        assert_eq!(samples.len(), 4);
+        assert!(samples
+            .chunks(2)
+            .all(|frame| (frame[0] - 0.5).abs() < 1e-3 && frame[1].abs() < 1e-6));
+    }
+
+    #[test]
+    fn empty_chain_passes_through() {
+        let source = EffectChain::new().apply(SamplesBuffer::new(1, 1000, vec![0.25f32, 0.5]));
+        assert_eq!(source.collect::<Vec<_>>(), vec![0.25, 0.5]);
+    }
+
+    #[test]
+    fn reverb_of_a_boxed_source() {
+        let chain = EffectChain::new().with(EffectSpec::Reverb(Duration::from_millis(2), 0.5));
+        let samples: Vec<f32> = chain
+            .apply(SamplesBuffer::new(1, 1000, vec![1.0f32, 0.0, 0.0, 0.0]))
+            .collect();
+        assert_eq!(&samples[..4], &[1.0, 0.0, 0.5, 0.0]);
+    }
+}
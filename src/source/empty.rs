@@ -40,7 +40,7 @@ where
 {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
-        None
+        Some(0)
     }
 
     #[inline]
@@ -95,8 +95,33 @@ where
         self.inner().total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.inner().total_len()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner_mut().try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.inner_mut().try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner().is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.inner().latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.inner().label()
+    }
 }
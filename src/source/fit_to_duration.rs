@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `FitToDuration` object.
+pub fn fit_to_duration<I>(input: I, duration: Duration) -> FitToDuration<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels();
+    let sample_rate = input.sample_rate();
+    let total_samples = samples_in(duration, channels, sample_rate);
+    FitToDuration {
+        input,
+        duration,
+        channels,
+        sample_rate,
+        total_samples,
+        remaining_samples: total_samples,
+        input_ended: false,
+    }
+}
+
+/// Number of samples in `duration`, rounded to the nearest whole frame.
+fn samples_in(duration: Duration, channels: u16, sample_rate: u32) -> usize {
+    let frames = (duration.as_nanos() * sample_rate as u128 + 500_000_000) / 1_000_000_000;
+    (frames as usize).saturating_mul(channels as usize)
+}
+
+/// A source that is cut off or padded with silence so it plays for exactly a given duration.
+///
+/// The number of samples is computed from the channels and sample rate of the first frame of
+/// the input. Sources of unknown length are played until they end or until the duration has
+/// passed, whichever comes first, and padded with silence if they ended early.
+#[derive(Clone, Debug)]
+pub struct FitToDuration<I> {
+    input: I,
+    duration: Duration,
+    channels: u16,
+    sample_rate: u32,
+    total_samples: usize,
+    remaining_samples: usize,
+    input_ended: bool,
+}
+
+impl<I> FitToDuration<I> {
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for FitToDuration<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.remaining_samples == 0 {
+            return None;
+        }
+        self.remaining_samples -= 1;
+
+        if !self.input_ended {
+            if let Some(sample) = self.input.next() {
+                return Some(sample);
+            }
+            self.input_ended = true;
+        }
+        Some(I::Item::zero_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining_samples, Some(self.remaining_samples))
+    }
+}
+
+impl<I> ExactSizeIterator for FitToDuration<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for FitToDuration<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.input_ended {
+            return Some(self.remaining_samples);
+        }
+        self.input
+            .current_frame_len()
+            .filter(|&len| len < self.remaining_samples)
+            .or(Some(self.remaining_samples))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.input_ended {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.input_ended {
+            self.sample_rate
+        } else {
+            self.input.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        Some(self.total_samples as u64)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.input.try_seek_exact(pos)?;
+        let skipped = samples_in(pos, self.channels, self.sample_rate);
+        self.remaining_samples = self.total_samples.saturating_sub(skipped);
+        self.input_ended = false;
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn pads_short_source() {
+        let inner = SamplesBuffer::new(2, 1000, vec![1i16, 2, 3, 4]);
+        let source = inner.fit_to_duration(Duration::from_millis(4));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(4)));
+        assert_eq!(source.total_len(), Some(8));
+        assert_eq!(source.len(), 8);
+        assert_eq!(source.collect::<Vec<_>>(), vec![1, 2, 3, 4, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn truncates_long_source() {
+        let inner = SamplesBuffer::new(1, 1000, vec![1i16, 2, 3, 4, 5]);
+        let source = inner.fit_to_duration(Duration::from_millis(3));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(3)));
+        assert_eq!(source.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn truncates_source_of_unknown_length() {
+        let source = SineWave::new(440.0).fit_to_duration(Duration::from_millis(250));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(250)));
+        assert_eq!(source.count(), 12000);
+    }
+
+    #[test]
+    fn seek_keeps_the_exact_length() {
+        let inner = SamplesBuffer::new(1, 1000, vec![1i16, 2, 3]);
+        let mut source = inner.fit_to_duration(Duration::from_millis(5));
+        source.try_seek(Duration::from_millis(2)).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), vec![3, 0, 0]);
+    }
+}
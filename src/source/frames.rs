@@ -0,0 +1,138 @@
+use crate::{Sample, Source};
+
+/// Internal function that builds a `Frames` object.
+pub fn frames<I>(input: I) -> Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Frames {
+        input,
+        frame: Vec::new(),
+    }
+}
+
+/// Iterator over the frames of a source: groups of one sample for every channel.
+///
+/// Each frame is as long as the channel count of the source at the moment the frame starts,
+/// so changes in the channel count at the end of a [`current_frame_len`] span are followed.
+/// A frame cut short, because the source or a span of it ends partway through, is padded
+/// with silence so every frame has a sample for every channel.
+///
+/// [`next_frame`](Frames::next_frame) lends out each frame without allocating, the
+/// `Iterator` implementation returns them as owned `Vec`s.
+///
+/// [`current_frame_len`]: Source::current_frame_len
+#[derive(Clone, Debug)]
+pub struct Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    frame: Vec<I::Item>,
+}
+
+impl<I> Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the next frame, with one sample for every channel in order, or `None` once
+    /// the source has ended.
+    #[inline]
+    pub fn next_frame(&mut self) -> Option<&[I::Item]> {
+        let channels = self.input.channels().max(1) as usize;
+        let available = match self.input.current_frame_len() {
+            Some(len) if len > 0 => len.min(channels),
+            _ => channels,
+        };
+
+        self.frame.clear();
+        self.frame.extend(self.input.by_ref().take(available));
+        if self.frame.is_empty() {
+            return None;
+        }
+        self.frame.resize(channels, I::Item::zero_value());
+        Some(&self.frame)
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Frames<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = Vec<I::Item>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        self.next_frame().map(<[I::Item]>::to_vec)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let channels = self.input.channels().max(1) as usize;
+        let (lower, upper) = self.input.size_hint();
+        (
+            lower.div_ceil(channels),
+            upper.map(|upper| upper.div_ceil(channels)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn groups_interleaved_samples() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4, 5, 6]);
+        let frames: Vec<Vec<i16>> = source.frames().collect();
+        assert_eq!(frames, vec![vec![1, 2], vec![3, 4], vec![5, 6]]);
+    }
+
+    #[test]
+    fn pads_last_frame() {
+        let mut frames = SamplesBuffer::new(3, 44100, vec![1.0f32, 2.0, 3.0, 4.0]).frames();
+        assert_eq!(frames.next_frame(), Some(&[1.0, 2.0, 3.0][..]));
+        assert_eq!(frames.next_frame(), Some(&[4.0, 0.0, 0.0][..]));
+        assert_eq!(frames.next_frame(), None);
+    }
+
+    #[test]
+    fn follows_channel_changes() {
+        // the queue only knows a new sound starts when the old one reports its end
+        let mono =
+            SamplesBuffer::new(1, 1000, vec![1i16, 2]).fit_to_duration(Duration::from_millis(2));
+        let stereo =
+            SamplesBuffer::new(2, 1000, vec![3i16, 4]).fit_to_duration(Duration::from_millis(1));
+        let (controller, queue) = crate::queue::queue(false);
+        controller.append(mono);
+        controller.append(stereo);
+
+        let frames: Vec<Vec<i16>> = queue.frames().collect();
+        assert_eq!(frames, vec![vec![1], vec![2], vec![3, 4]]);
+    }
+}
@@ -146,6 +146,34 @@ where
             Ok(())
         }
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        if let Some(source) = self.current_source.as_mut() {
+            source.try_seek_exact(pos)
+        } else {
+            Ok(pos)
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.current_source
+            .as_ref()
+            .is_some_and(|source| source.is_seekable())
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.current_source
+            .as_ref()
+            .map_or(0, |source| source.latency_samples())
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.current_source.as_ref()?.label()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `InspectEnd` object.
+pub fn inspect_end<I, F>(input: I, callback: F) -> InspectEnd<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnOnce(),
+{
+    InspectEnd {
+        input,
+        callback: Some(callback),
+    }
+}
+
+/// Calls a closure the first time the inner source returns `None`.
+///
+/// The closure is called at most once, even if `next` keeps being called after the end of
+/// the source has been reached.
+#[derive(Clone, Debug)]
+pub struct InspectEnd<I, F> {
+    input: I,
+    callback: Option<F>,
+}
+
+impl<I, F> InspectEnd<I, F> {
+    /// Returns true if the closure has already been called.
+    #[inline]
+    pub fn has_ended(&self) -> bool {
+        self.callback.is_none()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for InspectEnd<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnOnce(),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.input.next();
+        if next.is_none() {
+            if let Some(callback) = self.callback.take() {
+                callback();
+            }
+        }
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for InspectEnd<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnOnce(),
+{
+}
+
+impl<I, F> Source for InspectEnd<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnOnce(),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn called_once_at_end() {
+        let calls = Cell::new(0);
+        let inner = SamplesBuffer::new(1, 1, vec![10i16, -10, 20]);
+        let mut source = inner.inspect_end(|| calls.set(calls.get() + 1));
+
+        assert_eq!(source.next(), Some(10));
+        assert_eq!(source.next(), Some(-10));
+        assert_eq!(source.next(), Some(20));
+        assert_eq!(calls.get(), 0);
+        assert!(!source.has_ended());
+
+        assert_eq!(source.next(), None);
+        assert_eq!(calls.get(), 1);
+        assert!(source.has_ended());
+
+        assert_eq!(source.next(), None);
+        assert_eq!(source.next(), None);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn empty_source() {
+        let calls = Cell::new(0);
+        let inner = SamplesBuffer::<i16>::new(1, 1, vec![]);
+        let mut source = inner.inspect_end(|| calls.set(calls.get() + 1));
+
+        assert_eq!(source.next(), None);
+        assert_eq!(calls.get(), 1);
+    }
+}
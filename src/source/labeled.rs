@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Labeled` object.
+pub fn labeled<I>(input: I, label: String) -> Labeled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Labeled { input, label }
+}
+
+/// Gives a source a name, returned by [`Source::label`], to tell it apart while debugging.
+///
+/// The label shows in [`DynamicMixerController::active_labels`], so for example the mixer
+/// of an output stream can list the sounds that are playing.
+///
+/// [`DynamicMixerController::active_labels`]: crate::dynamic_mixer::DynamicMixerController::active_labels
+#[derive(Clone, Debug)]
+pub struct Labeled<I> {
+    input: I,
+    label: String,
+}
+
+impl<I> Labeled<I> {
+    /// Changes the label.
+    #[inline]
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Labeled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Labeled<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Labeled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [I::Item]) -> usize {
+        self.input.fill_buffer(out)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        Some(&self.label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn label_passes_through_effects() {
+        let source = SamplesBuffer::new(1, 48000, vec![1i16, 2])
+            .labeled("footsteps")
+            .amplify(0.5)
+            .fade_in(std::time::Duration::from_millis(1));
+        assert_eq!(source.label(), Some("footsteps"));
+        assert_eq!(SamplesBuffer::new(1, 48000, vec![1i16]).label(), None);
+    }
+}
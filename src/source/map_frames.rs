@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use crate::source::Frames;
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `MapFrames` object.
+pub fn map_frames<I, F>(input: I, map: F) -> MapFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut [I::Item]),
+{
+    MapFrames {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        input: input.frames(),
+        map,
+        frame: Vec::new(),
+        position: 0,
+    }
+}
+
+/// Passes every frame of the input through a closure that can change it in place, see
+/// [`Source::map_frames`].
+///
+/// Frames are grouped like [`Frames`] does: the closure always gets one sample for every
+/// channel, a last frame cut short is padded with silence first.
+#[derive(Clone)]
+pub struct MapFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: Frames<I>,
+    map: F,
+    // The current frame, after the closure, and the position of the next sample in it.
+    frame: Vec<I::Item>,
+    position: usize,
+    // The format of the current frame.
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I, F> MapFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+
+    #[inline]
+    fn in_frame(&self) -> bool {
+        self.position < self.frame.len()
+    }
+}
+
+impl<I, F> Iterator for MapFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut [I::Item]),
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.in_frame() {
+            let frame = self.input.next_frame()?;
+            self.frame.clear();
+            self.frame.extend_from_slice(frame);
+            self.position = 0;
+            self.channels = self.frame.len() as u16;
+            self.sample_rate = self.input.inner().sample_rate();
+            (self.map)(&mut self.frame);
+        }
+        let sample = self.frame[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left_in_frame = self.frame.len() - self.position;
+        let channels = self.inner().channels().max(1) as usize;
+        let (min, max) = self.inner().size_hint();
+        (
+            min.saturating_add(left_in_frame),
+            max.and_then(|max| {
+                max.div_ceil(channels)
+                    .checked_mul(channels)?
+                    .checked_add(left_in_frame)
+            }),
+        )
+    }
+}
+
+impl<I, F> Source for MapFrames<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(&mut [I::Item]),
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let left_in_frame = self.frame.len() - self.position;
+        self.inner()
+            .current_frame_len()
+            .map(|len| len + left_in_frame)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.in_frame() {
+            self.channels
+        } else {
+            self.inner().channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.in_frame() {
+            self.sample_rate
+        } else {
+            self.inner().sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner().total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.inner_mut().try_seek_exact(pos)?;
+        self.frame.clear();
+        self.position = 0;
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner().is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.inner().latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.inner().label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn swaps_left_and_right() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, 2, 3, 4]).map_frames(|frame| {
+            frame.swap(0, 1);
+        });
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.size_hint(), (4, Some(4)));
+        assert_eq!(source.collect::<Vec<_>>(), vec![2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn gets_padded_frames() {
+        let source =
+            SamplesBuffer::new(3, 44100, vec![1.0f32, 2.0, 3.0, 4.0]).map_frames(|frame| {
+                assert_eq!(frame.len(), 3);
+                let sum: f32 = frame.iter().sum();
+                frame.fill(sum);
+            });
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            vec![6.0, 6.0, 6.0, 4.0, 4.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn can_be_played_in_a_sink() {
+        fn assert_send<S: Send>(_: S) {}
+        let mut gain = 0.5;
+        assert_send(
+            SamplesBuffer::new(1, 44100, vec![1.0f32]).map_frames(move |frame| {
+                frame[0] *= gain;
+                gain *= 0.5;
+            }),
+        );
+    }
+}
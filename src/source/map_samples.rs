@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `MapSamples` object.
+pub fn map_samples<I, F>(input: I, map: F) -> MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    MapSamples { input, map }
+}
+
+/// Passes every sample of the input through a closure, see [`Source::map_samples`].
+#[derive(Clone)]
+pub struct MapSamples<I, F> {
+    input: I,
+    map: F,
+}
+
+impl<I, F> MapSamples<I, F> {
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, F> Iterator for MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next().map(&mut self.map)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, F> ExactSizeIterator for MapSamples<I, F>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+}
+
+impl<I, F> Source for MapSamples<I, F>
+where
+    I: Source,
+    I::Item: Sample,
+    F: FnMut(I::Item) -> I::Item,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn maps_every_sample() {
+        let source = SamplesBuffer::new(2, 44100, vec![1i16, -2, 3, -4]).map_samples(|s| s * 2);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), 44100);
+        assert_eq!(source.collect::<Vec<_>>(), vec![2, -4, 6, -8]);
+    }
+
+    #[test]
+    fn passes_seeking_through() {
+        let mut source = SamplesBuffer::new(1, 10, vec![1.0f32, 2.0, 3.0]).map_samples(|s| -s);
+        assert!(source.is_seekable());
+        source
+            .try_seek(std::time::Duration::from_millis(200))
+            .unwrap();
+        assert_eq!(source.next(), Some(-3.0));
+    }
+}
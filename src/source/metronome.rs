@@ -0,0 +1,224 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::atomic::AtomicCell;
+
+use crate::Source;
+
+use super::SeekError;
+
+const SAMPLE_RATE: u32 = 48000;
+const CLICK_SAMPLES: u32 = SAMPLE_RATE * 30 / 1000;
+const CLICK_DECAY_SAMPLES: f32 = SAMPLE_RATE as f32 * 0.005;
+
+/// An infinite source that clicks on every beat, with an accented click on the first beat
+/// of every bar.
+///
+/// The timing is sample accurate and does not drift, even over hours of playback. The tempo
+/// can be changed while playing through a [`Tempo`] handle.
+///
+/// Always has a rate of 48kHz and one channel.
+#[derive(Clone, Debug)]
+pub struct Metronome {
+    tempo: Tempo,
+    beats_per_bar: u32,
+    // Progress towards the next beat, from 0 up to 1.
+    phase: f64,
+    beat_in_bar: u32,
+    samples_since_click: u32,
+}
+
+impl Metronome {
+    /// Builds a metronome playing `bpm` beats per minute with `beats_per_bar` beats in a bar.
+    ///
+    /// The first click is the accented downbeat. A `beats_per_bar` of 0 or 1 accents every
+    /// beat.
+    #[inline]
+    pub fn new(bpm: f32, beats_per_bar: u32) -> Metronome {
+        Metronome {
+            tempo: Tempo(Arc::new(AtomicCell::new(bpm))),
+            beats_per_bar: beats_per_bar.max(1),
+            phase: 0.0,
+            beat_in_bar: 0,
+            samples_since_click: 0,
+        }
+    }
+
+    /// Returns a handle that can change the tempo while the metronome plays.
+    #[inline]
+    pub fn tempo(&self) -> Tempo {
+        self.tempo.clone()
+    }
+
+    /// Returns the beat within the bar that was last clicked, starting at 0 for the downbeat.
+    #[inline]
+    pub fn beat_in_bar(&self) -> u32 {
+        self.beat_in_bar
+    }
+}
+
+/// Shared handle to the tempo of a [`Metronome`].
+#[derive(Clone, Debug)]
+pub struct Tempo(Arc<AtomicCell<f32>>);
+
+impl Tempo {
+    /// Returns the tempo in beats per minute.
+    #[inline]
+    pub fn bpm(&self) -> f32 {
+        self.0.load()
+    }
+
+    /// Changes the tempo. The next beat is scheduled from the new tempo, starting from the
+    /// progress already made towards it.
+    #[inline]
+    pub fn set_bpm(&self, bpm: f32) {
+        self.0.store(bpm);
+    }
+}
+
+impl Iterator for Metronome {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = if self.samples_since_click < CLICK_SAMPLES {
+            let (freq, volume) = if self.beat_in_bar == 0 {
+                (1600.0, 1.0)
+            } else {
+                (800.0, 0.6)
+            };
+            let t = self.samples_since_click as f32;
+            let decay = (-t / CLICK_DECAY_SAMPLES).exp();
+            volume * decay * (2.0 * PI * freq * t / SAMPLE_RATE as f32).sin()
+        } else {
+            0.0
+        };
+        self.samples_since_click = self.samples_since_click.saturating_add(1);
+
+        // The remainder is kept when a beat is reached, so rounding errors do not add up.
+        let bpm = self.tempo.bpm().max(0.0) as f64;
+        self.phase += bpm / 60.0 / SAMPLE_RATE as f64;
+        if self.phase >= 1.0 {
+            self.phase -= self.phase.floor();
+            self.beat_in_bar = (self.beat_in_bar + 1) % self.beats_per_bar;
+            self.samples_since_click = 0;
+        }
+
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl Source for Metronome {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Jumps to the beat at `pos`, assuming the current tempo has been used all along.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let bpm = self.tempo.bpm().max(0.0) as f64;
+        let beats = pos.as_secs_f64() * bpm / 60.0;
+        self.beat_in_bar = (beats.floor() as u64 % self.beats_per_bar as u64) as u32;
+        self.phase = beats.fract();
+        let samples_per_beat = if bpm > 0.0 {
+            60.0 / bpm * SAMPLE_RATE as f64
+        } else {
+            0.0
+        };
+        self.samples_since_click = (self.phase * samples_per_beat) as u32;
+        Ok(())
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::{Metronome, Source};
+
+    /// Returns the indices at which a click starts.
+    fn click_starts(source: Metronome, samples: usize) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut previous = 0.0f32;
+        for (i, sample) in source.take(samples).enumerate() {
+            // every click starts at a zero crossing going up, after silence
+            if previous == 0.0 && sample > 0.0 {
+                starts.push(i - 1);
+            }
+            previous = sample;
+        }
+        starts
+    }
+
+    #[test]
+    fn clicks_do_not_drift() {
+        let bpm = 97.0;
+        let samples_per_beat = 60.0 / bpm as f64 * 48000.0;
+        let minutes = 3;
+        let starts = click_starts(Metronome::new(bpm, 4), minutes * 60 * 48000);
+
+        assert_eq!(starts.len(), (minutes as f32 * bpm).ceil() as usize);
+        for (beat, start) in starts.into_iter().enumerate() {
+            let expected = beat as f64 * samples_per_beat;
+            assert!((start as f64 - expected).abs() <= 1.0, "beat {beat}");
+        }
+    }
+
+    #[test]
+    fn downbeat_is_accented() {
+        let source = Metronome::new(120.0, 3);
+        let peaks: Vec<f32> = source
+            .take(6 * 24000)
+            .collect::<Vec<_>>()
+            .chunks(24000)
+            .map(|beat| beat.iter().fold(0.0f32, |max, s| max.max(s.abs())))
+            .collect();
+
+        assert!(peaks[0] > peaks[1] && peaks[0] > peaks[2]);
+        assert!(peaks[3] > peaks[4] && peaks[3] > peaks[5]);
+        assert_eq!(peaks[1], peaks[2]);
+    }
+
+    #[test]
+    fn tempo_change() {
+        let mut source = Metronome::new(120.0, 4);
+        let tempo = source.tempo();
+        for _ in 0..24000 {
+            source.next();
+        }
+        assert_eq!(source.beat_in_bar(), 1);
+
+        tempo.set_bpm(240.0);
+        assert_eq!(tempo.bpm(), 240.0);
+        for _ in 0..12000 {
+            source.next();
+        }
+        assert_eq!(source.beat_in_bar(), 2);
+        assert_eq!(source.total_duration(), None);
+    }
+}
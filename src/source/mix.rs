@@ -17,13 +17,23 @@ where
     let channels = input1.channels();
     let rate = input1.sample_rate();
 
+    let input1 = UniformSourceIterator::new(input1, channels, rate);
+    let input2 = UniformSourceIterator::new(input2, channels, rate);
+    // delay the input with the lower latency so both stay in phase
+    let latency1 = input1.latency_samples();
+    let latency2 = input2.latency_samples();
     Mix {
-        input1: UniformSourceIterator::new(input1, channels, rate),
-        input2: UniformSourceIterator::new(input2, channels, rate),
+        input1,
+        input2,
+        delay1: latency2.saturating_sub(latency1) * channels as usize,
+        delay2: latency1.saturating_sub(latency2) * channels as usize,
     }
 }
 
-/// Filter that modifies each sample by a given value.
+/// Plays two sources at the same time, see [`Source::mix`].
+///
+/// When one of the sources has a higher [`latency_samples`](Source::latency_samples), the
+/// other is delayed by the difference so the two stay in phase.
 #[derive(Clone)]
 pub struct Mix<I1, I2>
 where
@@ -34,6 +44,9 @@ where
 {
     input1: UniformSourceIterator<I1, I1::Item>,
     input2: UniformSourceIterator<I2, I2::Item>,
+    // Silent samples left to play before each input, to compensate for latency.
+    delay1: usize,
+    delay2: usize,
 }
 
 impl<I1, I2> Iterator for Mix<I1, I2>
@@ -47,8 +60,8 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I1::Item> {
-        let s1 = self.input1.next();
-        let s2 = self.input2.next();
+        let s1 = delayed(&mut self.delay1, &mut self.input1);
+        let s2 = delayed(&mut self.delay2, &mut self.input2);
 
         match (s1, s2) {
             (Some(s1), Some(s2)) => Some(s1.saturating_add(CpalSample::from_sample(s2))),
@@ -60,8 +73,8 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let s1 = self.input1.size_hint();
-        let s2 = self.input2.size_hint();
+        let s1 = delayed_size_hint(self.delay1, self.input1.size_hint());
+        let s2 = delayed_size_hint(self.delay2, self.input2.size_hint());
 
         let min = cmp::max(s1.0, s2.0);
         let max = match (s1.1, s2.1) {
@@ -112,15 +125,31 @@ where
 
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
+        let samples_per_sec = self.channels() as u64 * self.sample_rate() as u64;
+        let delay = |samples: usize| {
+            Duration::from_nanos(samples as u64 * 1_000_000_000 / samples_per_sec.max(1))
+        };
         let f1 = self.input1.total_duration();
         let f2 = self.input2.total_duration();
 
         match (f1, f2) {
-            (Some(f1), Some(f2)) => Some(cmp::max(f1, f2)),
+            (Some(f1), Some(f2)) => {
+                Some(cmp::max(f1 + delay(self.delay1), f2 + delay(self.delay2)))
+            }
             _ => None,
         }
     }
 
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        cmp::max(self.input1.latency_samples(), self.input2.latency_samples())
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input1.label().or_else(|| self.input2.label())
+    }
+
     /// Will only attempt a seek if both underlying sources support seek.
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
@@ -142,3 +171,96 @@ where
         // res
     }
 }
+
+/// Returns a silent sample while `delay` is not used up, then the samples of `input`.
+#[inline]
+fn delayed<S>(delay: &mut usize, input: &mut S) -> Option<S::Item>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    if *delay > 0 {
+        *delay -= 1;
+        return Some(S::Item::zero_value());
+    }
+    input.next()
+}
+
+#[inline]
+fn delayed_size_hint(delay: usize, (min, max): (usize, Option<usize>)) -> (usize, Option<usize>) {
+    (
+        min.saturating_add(delay),
+        max.and_then(|max| max.checked_add(delay)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    /// Plays a buffer that already contains the lead in of an effect with `latency`.
+    struct Latent {
+        input: SamplesBuffer<f32>,
+        latency: usize,
+    }
+
+    impl Iterator for Latent {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.input.next()
+        }
+    }
+
+    impl Source for Latent {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            self.input.channels()
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.input.sample_rate()
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            self.input.total_duration()
+        }
+
+        fn latency_samples(&self) -> usize {
+            self.latency
+        }
+    }
+
+    #[test]
+    fn dry_and_wet_stay_in_phase() {
+        let dry = SamplesBuffer::new(2, 1000, vec![1.0f32, -1.0, 2.0, -2.0]);
+        let wet = Latent {
+            input: SamplesBuffer::new(2, 1000, vec![0.0f32, 0.0, 1.0, -1.0, 2.0, -2.0]),
+            latency: 1,
+        };
+
+        let mixed = dry.mix(wet);
+        assert_eq!(mixed.latency_samples(), 1);
+        assert_eq!(mixed.total_duration(), Some(Duration::from_millis(3)));
+        assert_eq!(
+            mixed.collect::<Vec<_>>(),
+            vec![0.0, 0.0, 2.0, -2.0, 4.0, -4.0]
+        );
+    }
+
+    #[test]
+    fn latency_of_the_first_input_delays_the_second() {
+        let wet = Latent {
+            input: SamplesBuffer::new(1, 1000, vec![0.0f32, 0.0, 1.0]),
+            latency: 2,
+        };
+        let dry = SamplesBuffer::new(1, 1000, vec![1.0f32]);
+        assert_eq!(wet.mix(dry).collect::<Vec<_>>(), vec![0.0, 0.0, 2.0]);
+    }
+}
@@ -6,59 +6,91 @@ use cpal::FromSample;
 
 use crate::Sample;
 
-pub use self::amplify::Amplify;
+pub use self::amplify::{Amplify, Clipping};
 pub use self::blt::BltFilter;
 pub use self::buffered::Buffered;
+pub use self::bypassable::Bypassable;
+pub use self::channel_delay::ChannelDelay;
 pub use self::channel_volume::ChannelVolume;
+pub use self::compressor::{Compressor, CompressorSettings, SidechainCompressor};
 pub use self::crossfade::Crossfade;
 pub use self::delay::Delay;
 pub use self::done::Done;
+pub use self::effect_chain::{EffectChain, EffectSpec};
 pub use self::empty::Empty;
 pub use self::empty_callback::EmptyCallback;
 pub use self::fadein::FadeIn;
 pub use self::fadeout::FadeOut;
+pub use self::fit_to_duration::FitToDuration;
+pub use self::frames::Frames;
 pub use self::from_factory::{from_factory, FromFactoryIter};
 pub use self::from_iter::{from_iter, FromIter};
+pub use self::inspect_end::InspectEnd;
+pub use self::labeled::Labeled;
 pub use self::linear_ramp::LinearGainRamp;
+pub use self::map_frames::MapFrames;
+pub use self::map_samples::MapSamples;
+pub use self::metronome::{Metronome, Tempo};
 pub use self::mix::Mix;
+pub use self::normalize::Normalized;
+pub use self::pan::{Pan, PanPosition};
 pub use self::pausable::Pausable;
 pub use self::periodic::PeriodicAccess;
 pub use self::position::TrackPosition;
+pub use self::rechunk::Rechunk;
+pub use self::remap_channels::{RemapChannels, RemapError};
 pub use self::repeat::Repeat;
 pub use self::samples_converter::SamplesConverter;
 pub use self::sine::SineWave;
 pub use self::skip::SkipDuration;
 pub use self::skippable::Skippable;
-pub use self::spatial::Spatial;
+pub use self::smoothed_param::{SmoothedParam, SmoothingMode};
+pub use self::spatial::{DistanceModel, Spatial};
 pub use self::speed::Speed;
 pub use self::stoppable::Stoppable;
 pub use self::take::TakeDuration;
 pub use self::uniform::UniformSourceIterator;
-pub use self::zero::Zero;
+pub use self::zero::{silence, Zero};
 
 mod amplify;
 mod blt;
 mod buffered;
+mod bypassable;
+mod channel_delay;
 mod channel_volume;
+mod compressor;
 mod crossfade;
 mod delay;
 mod done;
+mod effect_chain;
 mod empty;
 mod empty_callback;
 mod fadein;
 mod fadeout;
+mod fit_to_duration;
+mod frames;
 mod from_factory;
 mod from_iter;
+mod inspect_end;
+mod labeled;
 mod linear_ramp;
+mod map_frames;
+mod map_samples;
+mod metronome;
 mod mix;
+mod normalize;
+mod pan;
 mod pausable;
 mod periodic;
 mod position;
+mod rechunk;
+mod remap_channels;
 mod repeat;
 mod samples_converter;
 mod sine;
 mod skip;
 mod skippable;
+mod smoothed_param;
 mod spatial;
 mod speed;
 mod stoppable;
@@ -157,6 +189,46 @@ where
     /// `None` indicates at the same time "infinite" or "unknown".
     fn total_duration(&self) -> Option<Duration>;
 
+    /// Returns the total number of samples of this source, counting every channel, if known.
+    ///
+    /// Unlike [`total_duration`](Source::total_duration) this is exact, which matters for
+    /// sample accurate looping and seeking. By default it is derived from the total duration,
+    /// the sample rate and the channel count, rounded to a whole frame. Sources that know
+    /// their length, like decoders and buffers, return it as is, and the sources built by
+    /// the methods of this trait work it out from the length of their input where they can.
+    ///
+    /// `None` indicates at the same time "infinite" or "unknown".
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        let duration = self.total_duration()?;
+        let frames =
+            (duration.as_nanos() * self.sample_rate() as u128 + 500_000_000) / 1_000_000_000;
+        Some((frames as u64).saturating_mul(self.channels() as u64))
+    }
+
+    /// Fills `out` with the next samples of this source and returns how many were written.
+    ///
+    /// This produces the same samples as calling `next` repeatedly, but sources that keep
+    /// their samples in memory, like decoders and
+    /// [`SamplesBuffer`](crate::buffer::SamplesBuffer), copy them over in bulk. Fewer than
+    /// `out.len()` samples are only written once the source has ended.
+    ///
+    /// The samples can span several frames, use [`Source::current_frame_len`] to limit
+    /// `out` if changes of the channel count or sample rate need to be handled.
+    // Not named `next_chunk` as that would clash with the unstable `Iterator::next_chunk`.
+    #[inline]
+    fn fill_buffer(&mut self, out: &mut [Self::Item]) -> usize {
+        let mut filled = 0;
+        for slot in out.iter_mut() {
+            match self.next() {
+                Some(sample) => *slot = sample,
+                None => break,
+            }
+            filled += 1;
+        }
+        filled
+    }
+
     /// Stores the source in a buffer in addition to returning it. This iterator can be cloned.
 
     #[inline]
@@ -167,6 +239,77 @@ where
         buffered::buffered(self)
     }
 
+    /// Groups the samples of this source into frames, one sample for every channel, so
+    /// effects and analysis do not need to keep track of the interleaving.
+    ///
+    /// See [`Frames`] for how changing channel counts and incomplete frames are handled.
+    #[inline]
+    fn frames(self) -> Frames<Self>
+    where
+        Self: Sized,
+    {
+        frames::frames(self)
+    }
+
+    /// Re-emits this source in blocks of `frames_per_block` frames, for consumers that need
+    /// fixed size blocks such as encoders or FFT based effects. The last block is padded with
+    /// silence.
+    ///
+    /// See [`Rechunk`] for how changes in the channel count or sample rate are handled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frames_per_block` is zero.
+    #[inline]
+    fn rechunk(self, frames_per_block: usize) -> Rechunk<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        rechunk::rechunk(self, frames_per_block)
+    }
+
+    /// Passes every sample through `map`, for trying out an effect without implementing
+    /// `Source`. The channels, sample rate, duration and seeking of this source are kept.
+    ///
+    /// The result can be played in a [`Sink`](crate::Sink) as long as `map` is `Send`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::buffer::SamplesBuffer;
+    /// use rodio::Source;
+    ///
+    /// // a crude distortion
+    /// let source = SamplesBuffer::new(1, 44100, vec![0.1f32, 0.9, -0.9])
+    ///     .map_samples(|sample| sample.clamp(-0.5, 0.5));
+    /// assert_eq!(source.collect::<Vec<_>>(), vec![0.1, 0.5, -0.5]);
+    /// ```
+    #[inline]
+    fn map_samples<F>(self, map: F) -> MapSamples<Self, F>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        F: FnMut(Self::Item) -> Self::Item,
+    {
+        map_samples::map_samples(self, map)
+    }
+
+    /// Passes every frame, one sample for every channel, through `map` which changes it in
+    /// place. Like [`map_samples`](Source::map_samples) but for effects that need all
+    /// channels at once, such as panning or stereo widening.
+    ///
+    /// See [`MapFrames`] for how incomplete frames are handled.
+    #[inline]
+    fn map_frames<F>(self, map: F) -> MapFrames<Self, F>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        F: FnMut(&mut [Self::Item]),
+    {
+        map_frames::map_frames(self, map)
+    }
+
     /// Mixes this source with another one.
     #[inline]
     fn mix<S>(self, other: S) -> Mix<Self, S>
@@ -200,6 +343,19 @@ where
         take::take_duration(self, duration)
     }
 
+    /// Makes this source play for exactly `duration`, cutting it off if it is longer and
+    /// padding it with silence if it is shorter.
+    ///
+    /// `total_duration` always returns `duration`. Sources of unknown length are cut off at
+    /// `duration` and padded if they turn out to end earlier.
+    #[inline]
+    fn fit_to_duration(self, duration: Duration) -> FitToDuration<Self>
+    where
+        Self: Sized,
+    {
+        fit_to_duration::fit_to_duration(self, duration)
+    }
+
     /// Delays the sound by a certain duration.
     ///
     /// The rate and channels of the silence will use the same format as the first frame of the
@@ -212,6 +368,24 @@ where
         delay::delay(self, duration)
     }
 
+    /// Delays each channel by its own duration, one entry in `delays` per channel.
+    ///
+    /// Delays do not have to be a whole number of samples, fractional delays are linearly
+    /// interpolated. Delaying one ear by a few hundred microseconds, the Haas effect, makes a
+    /// sound appear to come from the other side.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of delays does not match the number of channels.
+    #[inline]
+    fn delay_channels(self, delays: &[Duration]) -> ChannelDelay<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        channel_delay::channel_delay(self, delays)
+    }
+
     /// Immediately skips a certain duration of this source.
     ///
     /// If the specified duration is longer than the source itself, `skip_duration` will skip to the end of the source.
@@ -232,6 +406,145 @@ where
         amplify::amplify(self, value)
     }
 
+    /// Amplifies the sound by the given value and keeps the result within full scale, so
+    /// boosting a quiet sound does not make it clip harshly further down the line.
+    ///
+    /// See [`Clipping`] for the difference between clamping and saturating.
+    #[inline]
+    fn amplify_clamped(self, value: f32, clipping: Clipping) -> Amplify<Self>
+    where
+        Self: Sized,
+    {
+        amplify::amplify_clamped(self, value, clipping)
+    }
+
+    /// Scales the sound so its loudest sample is at `target_peak`, where `1.0` is full
+    /// scale, to level sound effects of different loudness. Targets above full scale are
+    /// lowered to it.
+    ///
+    /// The sound is read completely and kept in memory first. It has to be known to end, an
+    /// [`ExactSizeIterator`] such as a [`SamplesBuffer`](crate::buffer::SamplesBuffer), see
+    /// [`Normalized`].
+    #[inline]
+    fn normalize_peak(self, target_peak: f32) -> Normalized<Self>
+    where
+        Self: Sized + ExactSizeIterator,
+        Self::Item: Sample,
+    {
+        normalize::normalize_peak(self, target_peak)
+    }
+
+    /// Scales the sound so its RMS level is at `target_rms`, where `1.0` is full scale. This
+    /// levels perceived loudness better than [`normalize_peak`](Source::normalize_peak),
+    /// but the gain is limited to what keeps the loudest sample within full scale.
+    ///
+    /// The sound is read completely and kept in memory first. It has to be known to end, an
+    /// [`ExactSizeIterator`] such as a [`SamplesBuffer`](crate::buffer::SamplesBuffer), see
+    /// [`Normalized`].
+    #[inline]
+    fn normalize_rms(self, target_rms: f32) -> Normalized<Self>
+    where
+        Self: Sized + ExactSizeIterator,
+        Self::Item: Sample,
+    {
+        normalize::normalize_rms(self, target_rms)
+    }
+
+    /// Compresses the dynamic range of the sound: while it is louder than the threshold its
+    /// volume is lowered, by an amount set by the ratio.
+    ///
+    /// See [`CompressorSettings`] for the parameters.
+    #[inline]
+    fn compressor(self, settings: CompressorSettings) -> Compressor<Self>
+    where
+        Self: Sized,
+    {
+        compressor::compressor(self, settings)
+    }
+
+    /// Compresses this sound based on the level of another one, the key. Useful to duck
+    /// music under a voice: pass the voice as the key and mix it with the result.
+    ///
+    /// The key is only listened to, it is not part of the output. See [`CompressorSettings`]
+    /// for the parameters.
+    #[inline]
+    fn compressor_sidechain<K>(
+        self,
+        settings: CompressorSettings,
+        key: K,
+    ) -> SidechainCompressor<Self, K>
+    where
+        Self: Sized,
+        K: Source,
+        K::Item: Sample,
+        f32: FromSample<K::Item>,
+    {
+        compressor::compressor_sidechain(self, settings, key)
+    }
+
+    /// Places the sound at a fixed position between the left (`-1.0`) and right (`1.0`)
+    /// speaker using equal power panning. The result is always stereo.
+    ///
+    /// Inputs with several channels are mixed down to mono before being positioned. Use
+    /// [`Pan::position`] to move the sound while it plays.
+    #[inline]
+    fn pan(self, position: f32) -> Pan<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        pan::pan(self, position)
+    }
+
+    /// Routes the input channels to other output channels. `map` has an entry for every
+    /// output channel, holding the index of the input channel to play on it or `None` to keep
+    /// it silent.
+    ///
+    /// Sounds with fewer channels than the output device are played on its first channels.
+    /// To target other speakers of a surround device, remap to
+    /// [`OutputStream::channels`](crate::OutputStream::channels) channels. For example a mono
+    /// sound plays on the center speaker of a 5.1 device with
+    /// `remap_channels(vec![None, None, Some(0), None, None, None])`.
+    ///
+    /// Use [`try_remap_channels`](Source::try_remap_channels) to check the map against the
+    /// number of channels of the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `map` is empty, longer than `u16::MAX` or refers to a channel the input does
+    /// not have.
+    #[inline]
+    fn remap_channels(self, map: Vec<Option<u16>>) -> RemapChannels<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        remap_channels::remap_channels(self, map)
+    }
+
+    /// Like [`remap_channels`](Source::remap_channels), for an output with
+    /// `output_channels` channels, usually
+    /// [`OutputStream::channels`](crate::OutputStream::channels). A map for another number of
+    /// channels would otherwise be converted to the channels of the device and play on the
+    /// wrong speakers.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RemapError`] if `map` does not have `output_channels` entries, is empty,
+    /// is longer than `u16::MAX` or refers to a channel the input does not have.
+    #[inline]
+    fn try_remap_channels(
+        self,
+        map: Vec<Option<u16>>,
+        output_channels: u16,
+    ) -> Result<RemapChannels<Self>, RemapError>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        remap_channels::try_remap_channels(self, map, output_channels)
+    }
+
     /// Mixes this sound fading out with another sound fading in for the given duration.
     ///
     /// Only the crossfaded portion (beginning of self, beginning of other) is returned.
@@ -301,6 +614,30 @@ where
         periodic::periodic(self, period, access)
     }
 
+    /// Calls the `callback` closure once, the first time this source returns `None`.
+    ///
+    /// Unlike [`Sink::sleep_until_end`](crate::Sink::sleep_until_end) this does not require a
+    /// `Sink` and also works when iterating over the source manually, for example while
+    /// rendering offline. Calling `next` again after the end does not call `callback` again.
+    #[inline]
+    fn inspect_end<F>(self, callback: F) -> InspectEnd<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(),
+    {
+        inspect_end::inspect_end(self, callback)
+    }
+
+    /// Gives this source a name to tell it apart while debugging, see [`Labeled`].
+    #[inline]
+    fn labeled(self, label: impl Into<String>) -> Labeled<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        labeled::labeled(self, label.into())
+    }
+
     /// Changes the play speed of the sound. Does not adjust the samples, only the play speed.
     #[inline]
     fn speed(self, ratio: f32) -> Speed<Self>
@@ -331,6 +668,30 @@ where
         self.mix(echo)
     }
 
+    /// Applies the effect built by `effect` in a way that it can be bypassed while it plays,
+    /// to compare the sound with and without it. See [`Bypassable`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rodio::source::{SineWave, Source};
+    ///
+    /// let source = SineWave::new(440.0).bypassable(|input| input.low_pass(800));
+    /// let bypass = source.bypass_handle();
+    /// // after adding `source` to a sink, from any thread
+    /// bypass.store(true, std::sync::atomic::Ordering::Relaxed);
+    /// ```
+    #[inline]
+    fn bypassable<E, F>(self, effect: F) -> Bypassable<Self, E>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        E: Source<Item = Self::Item>,
+        F: FnOnce(Buffered<Self>) -> E,
+    {
+        bypassable::bypassable(self, effect)
+    }
+
     /// Converts the samples of this source to another type.
     #[inline]
     fn convert_samples<D>(self) -> SamplesConverter<Self, D>
@@ -429,9 +790,37 @@ where
         blt::high_pass_with_q(self, freq, q)
     }
 
-    // There is no `can_seek()` method as it is impossible to use correctly. Between
-    // checking if a source supports seeking and actually seeking the sink can
-    // switch to a new source.
+    /// Returns true if [`try_seek`](Source::try_seek) is supported, for example to decide
+    /// whether to show a scrub bar.
+    ///
+    /// A seek can still fail, for instance when the underlying file turns out to be corrupt.
+    /// By default this returns false. Sources that support seeking return true, sources
+    /// wrapping another one ask the one they wrap.
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    /// Returns how many samples per channel the output of this source lags behind its
+    /// input, for effects that need to look ahead or work on blocks.
+    ///
+    /// [`mix`](Source::mix) uses this to keep a dry and a processed version of the same
+    /// sound in phase: the branch with the lower latency is delayed to match the other.
+    /// By default this is zero. Effects that introduce latency add theirs to that of their
+    /// input, sources wrapping another one return the latency of the one they wrap.
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
+    /// Returns the name this source was given with [`labeled`](Source::labeled), if any.
+    ///
+    /// By default this is `None`. Sources wrapping another one return the label of the one
+    /// they wrap.
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        None
+    }
 
     /// Attempts to seek to a given position in the current source.
     ///
@@ -455,6 +844,57 @@ where
             underlying_source: std::any::type_name::<Self>(),
         })
     }
+
+    /// Seeks like [`try_seek`](Source::try_seek) and returns the position that was actually
+    /// reached.
+    ///
+    /// Not every position can be reached exactly: seeks land on a whole frame, saturate at
+    /// the end of the source and some formats can only seek to just before their end. A
+    /// progress display can use the returned position instead of assuming the requested one.
+    ///
+    /// By default this seeks and then returns `pos` unchanged. Decoders and buffers return
+    /// where they landed, the sources built by the methods of this trait pass that on,
+    /// [`speed`](Source::speed) for example scales it by its factor.
+    ///
+    /// # Errors
+    /// The same as for [`try_seek`](Source::try_seek).
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.try_seek(pos)?;
+        Ok(pos)
+    }
+
+    /// Seeks to a fraction of the [`total_duration`](Source::total_duration), from `0.0` for
+    /// the start to `1.0` for the end, and returns the position that was reached. Fractions
+    /// outside of that range are clamped.
+    ///
+    /// The position is rounded to the nearest frame, a fraction of `1.0` seeks to exactly the
+    /// end.
+    ///
+    /// # Errors
+    /// Returns [`SeekError::UnknownDuration`] if the source does not know its total duration,
+    /// otherwise the same as for [`try_seek`](Source::try_seek).
+    #[inline]
+    fn try_seek_fraction(&mut self, fraction: f32) -> Result<Duration, SeekError> {
+        let total = self.total_duration().ok_or(SeekError::UnknownDuration)?;
+        let pos = fraction_position(total, fraction, self.sample_rate());
+        self.try_seek_exact(pos)
+    }
+}
+
+/// The position at `fraction` of `total`, rounded to the nearest frame.
+fn fraction_position(total: Duration, fraction: f32, sample_rate: u32) -> Duration {
+    let fraction = if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0) as f64
+    };
+    if fraction == 1.0 || sample_rate == 0 {
+        return total.mul_f64(fraction);
+    }
+    let frame = (total.as_secs_f64() * fraction * sample_rate as f64).round() as u64;
+    let pos = Duration::from_nanos(frame * 1_000_000_000 / sample_rate as u64);
+    pos.min(total)
 }
 
 // We might add decoders requiring new error types, without non_exhaustive
@@ -478,6 +918,9 @@ pub enum SeekError {
     #[error("Error seeking in wav source: {0}")]
     /// The hound (wav) decoder ran into an issue
     HoundDecoder(std::io::Error),
+    /// Seeking to a fraction of the source needs its total duration, which is not known
+    #[error("The total duration of the source is not known")]
+    UnknownDuration,
     // Prefer adding an enum variant to using this. Its meant for end users their
     // own try_seek implementations
     /// Any other error probably in a custom Source
@@ -485,12 +928,66 @@ pub enum SeekError {
     Other(Box<dyn std::error::Error + Send>),
 }
 
+/// Occurs when a source reports a format that can not be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum FormatError {
+    /// The source reports zero channels.
+    #[error("The source has zero channels")]
+    ZeroChannels,
+    /// The source reports a sample rate of zero.
+    #[error("The source has a sample rate of zero")]
+    ZeroSampleRate,
+    /// The source reports a sample rate above [`FormatError::MAX_SAMPLE_RATE`].
+    #[error("The sample rate of the source, {0} Hz, is too high")]
+    SampleRateTooHigh(u32),
+}
+
+impl FormatError {
+    /// The highest sample rate a source can report when it is added to a mixer or sink.
+    ///
+    /// Sixteen times 192 kHz, which leaves room for speeding up high resolution audio
+    /// while rejecting the nonsense rates corrupt files can claim.
+    pub const MAX_SAMPLE_RATE: u32 = 3_072_000;
+
+    /// Checks the format the source reports for its current frame.
+    pub(crate) fn check<S>(source: &S) -> Result<(), FormatError>
+    where
+        S: Source + ?Sized,
+        S::Item: Sample,
+    {
+        if source.channels() == 0 {
+            Err(FormatError::ZeroChannels)
+        } else if source.sample_rate() == 0 {
+            Err(FormatError::ZeroSampleRate)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks the format of a source that is about to be played, which also rejects sample
+    /// rates above [`MAX_SAMPLE_RATE`](FormatError::MAX_SAMPLE_RATE).
+    pub(crate) fn check_new<S>(source: &S) -> Result<(), FormatError>
+    where
+        S: Source + ?Sized,
+        S::Item: Sample,
+    {
+        FormatError::check(source)?;
+        match source.sample_rate() {
+            rate if rate > FormatError::MAX_SAMPLE_RATE => {
+                Err(FormatError::SampleRateTooHigh(rate))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 impl SeekError {
     /// Will the source remain playing at its position before the seek or is it
     /// broken?
     pub fn source_intact(&self) -> bool {
         match self {
             SeekError::NotSupported { .. } => true,
+            SeekError::UnknownDuration => true,
             #[cfg(feature = "symphonia")]
             SeekError::SymphoniaDecoder(_) => false,
             #[cfg(feature = "wav")]
@@ -523,10 +1020,45 @@ macro_rules! source_pointer_impl {
                 (**self).total_duration()
             }
 
+            #[inline]
+            fn total_len(&self) -> Option<u64> {
+                (**self).total_len()
+            }
+
+            #[inline]
+            fn fill_buffer(&mut self, out: &mut [Self::Item]) -> usize {
+                (**self).fill_buffer(out)
+            }
+
             #[inline]
             fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
                 (**self).try_seek(pos)
             }
+
+            #[inline]
+            fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+                (**self).try_seek_exact(pos)
+            }
+
+            #[inline]
+            fn try_seek_fraction(&mut self, fraction: f32) -> Result<Duration, SeekError> {
+                (**self).try_seek_fraction(fraction)
+            }
+
+            #[inline]
+            fn is_seekable(&self) -> bool {
+                (**self).is_seekable()
+            }
+
+            #[inline]
+            fn latency_samples(&self) -> usize {
+                (**self).latency_samples()
+            }
+
+            #[inline]
+            fn label(&self) -> Option<&str> {
+                (**self).label()
+            }
         }
     };
 }
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::{Buffered, SeekError};
+
+/// Internal function that builds a `Normalized` object with the gain for `target_peak`.
+pub fn normalize_peak<I>(input: I, target_peak: f32) -> Normalized<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+    let input = input.buffered();
+    let (peak, _) = measure(input.clone());
+    let gain = if peak > 0.0 {
+        target_peak.clamp(0.0, 1.0) / peak
+    } else {
+        1.0
+    };
+    Normalized { input, gain }
+}
+
+/// Internal function that builds a `Normalized` object with the gain for `target_rms`.
+pub fn normalize_rms<I>(input: I, target_rms: f32) -> Normalized<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+    let input = input.buffered();
+    let (peak, rms) = measure(input.clone());
+    let gain = if rms > 0.0 {
+        // never louder than what brings the peak to full scale
+        (target_rms.max(0.0) / rms).min(1.0 / peak)
+    } else {
+        1.0
+    };
+    Normalized { input, gain }
+}
+
+/// Returns the absolute peak and the RMS level of all samples, full scale is `1.0`.
+fn measure<I>(input: I) -> (f32, f32)
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    let mut peak = 0.0f32;
+    let mut sum_of_squares = 0.0f64;
+    let mut count = 0u64;
+    for sample in input {
+        let value = sample.to_float_sample().to_sample::<f32>();
+        peak = peak.max(value.abs());
+        sum_of_squares += value as f64 * value as f64;
+        count += 1;
+    }
+    let rms = if count == 0 {
+        0.0
+    } else {
+        (sum_of_squares / count as f64).sqrt() as f32
+    };
+    (peak, rms)
+}
+
+/// A source scaled so its loudest sample, or its RMS level, matches a target, to level
+/// clips of different loudness. The gain never makes the source clip.
+///
+/// The whole input is read and kept in memory to measure it before the first sample plays.
+/// The input has to be an [`ExactSizeIterator`], so it is known to end, like a
+/// [`SamplesBuffer`](crate::buffer::SamplesBuffer). Decode a clip into one first:
+///
+/// ```no_run
+/// use rodio::buffer::SamplesBuffer;
+/// use rodio::{Decoder, Source};
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let decoder = Decoder::new(BufReader::new(File::open("click.ogg").unwrap())).unwrap();
+/// let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+/// let clip = SamplesBuffer::new(channels, sample_rate, decoder.collect::<Vec<i16>>());
+/// let clip = clip.normalize_peak(0.8);
+/// ```
+///
+/// Measuring again every time a sound is loaded can be avoided by caching
+/// [`gain`](Normalized::gain) and applying it with [`Source::amplify`] next time.
+pub struct Normalized<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: Buffered<I>,
+    gain: f32,
+}
+
+impl<I> Clone for Normalized<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn clone(&self) -> Normalized<I> {
+        Normalized {
+            input: self.input.clone(),
+            gain: self.gain,
+        }
+    }
+}
+
+impl<I> Normalized<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the gain applied to the input.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Returns a reference to the buffered input.
+    #[inline]
+    pub fn inner(&self) -> &Buffered<I> {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the buffered input.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut Buffered<I> {
+        &mut self.input
+    }
+
+    /// Returns the buffered input.
+    #[inline]
+    pub fn into_inner(self) -> Buffered<I> {
+        self.input
+    }
+}
+
+impl<I> Iterator for Normalized<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next().map(|value| value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Normalized<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Normalized<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn peak_to_target() {
+        let source = SamplesBuffer::new(1, 48000, vec![0.25f32, -0.5, 0.1]).normalize_peak(0.8);
+        assert!((source.gain() - 1.6).abs() < 1e-6);
+        let samples: Vec<f32> = source.collect();
+        assert!((samples[1] + 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn never_clips() {
+        let source = SamplesBuffer::new(1, 48000, vec![0.5f32, -0.5]).normalize_peak(2.0);
+        assert_eq!(source.gain(), 2.0);
+
+        // an RMS of 0.5 would need a gain of 5, limited by the peak
+        let source = SamplesBuffer::new(1, 48000, vec![0.5f32, 0.0, 0.0, 0.0]).normalize_rms(0.5);
+        assert_eq!(source.gain(), 2.0);
+        assert!(source.map(f32::abs).all(|sample| sample <= 1.0));
+    }
+
+    #[test]
+    fn rms_to_target() {
+        let source = SamplesBuffer::new(2, 48000, vec![i16::MAX / 10; 100]).normalize_rms(0.2);
+        assert!((source.gain() - 2.0).abs() < 1e-3);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.count(), 100);
+    }
+
+    #[test]
+    fn buffered_input() {
+        let input = SamplesBuffer::new(1, 48000, vec![0.25f32, -0.1]).buffered();
+        let source = input.normalize_peak(0.5);
+        assert!((source.gain() - 2.0).abs() < 1e-6);
+        assert_eq!(source.len(), 2);
+    }
+
+    #[test]
+    fn silence_is_unchanged() {
+        let source = SamplesBuffer::new(1, 48000, vec![0.0f32; 10]).normalize_rms(0.5);
+        assert_eq!(source.gain(), 1.0);
+    }
+}
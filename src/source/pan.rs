@@ -0,0 +1,250 @@
+use std::f32::consts::FRAC_PI_4;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::atomic::AtomicCell;
+
+use crate::source::ChannelVolume;
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Pan` object.
+pub fn pan<I>(input: I, position: f32) -> Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut pan = Pan {
+        input: ChannelVolume::new(input, vec![0.0; 2]),
+        position: PanPosition(Arc::new(AtomicCell::new(position.clamp(-1.0, 1.0)))),
+        applied: None,
+        channel: 0,
+    };
+    pan.apply_position();
+    pan
+}
+
+/// Places the input at a position between the left and right speaker, using equal power
+/// panning.
+///
+/// The output is always stereo. Inputs with more than one channel, stereo included, are
+/// mixed down to mono first and that mono signal is re-panned, so a position of `0.0`
+/// plays the average of all channels on both speakers.
+#[derive(Clone)]
+pub struct Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: ChannelVolume<I>,
+    position: PanPosition,
+    // The position and number of input channels the volumes were last computed for.
+    applied: Option<(f32, u16)>,
+    // The output channel of the next sample.
+    channel: u16,
+}
+
+impl<I> Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle that can move the source while it plays.
+    #[inline]
+    pub fn position(&self) -> PanPosition {
+        self.position.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+
+    fn apply_position(&mut self) {
+        let position = self.position.get();
+        let channels = self.input.inner().channels().max(1);
+        if self.applied == Some((position, channels)) {
+            return;
+        }
+
+        // `ChannelVolume` sums the input channels, scale back down to their average
+        let angle = (position + 1.0) * FRAC_PI_4;
+        let scale = 1.0 / channels as f32;
+        self.input.set_volume(0, angle.cos() * scale);
+        self.input.set_volume(1, angle.sin() * scale);
+        self.applied = Some((position, channels));
+    }
+}
+
+/// Shared handle to the stereo position of a [`Pan`] source.
+#[derive(Clone, Debug)]
+pub struct PanPosition(Arc<AtomicCell<f32>>);
+
+impl PanPosition {
+    /// Returns the position, from `-1.0` for fully left to `1.0` for fully right.
+    #[inline]
+    pub fn get(&self) -> f32 {
+        self.0.load()
+    }
+
+    /// Moves the source, from `-1.0` for fully left to `1.0` for fully right. Values outside
+    /// of that range are clamped.
+    ///
+    /// The new position is used from the next frame onwards.
+    #[inline]
+    pub fn set(&self, position: f32) {
+        self.0.store(position.clamp(-1.0, 1.0));
+    }
+}
+
+impl<I> Iterator for Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.apply_position();
+        }
+        self.channel = (self.channel + 1) % 2;
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Pan<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn mono_is_panned_with_equal_power() {
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32]).pan(0.0);
+        assert_eq!(source.channels(), 2);
+        let samples: Vec<f32> = source.collect();
+        assert_eq!(samples.len(), 2);
+        assert_close(samples[0], std::f32::consts::FRAC_1_SQRT_2);
+        assert_close(samples[1], std::f32::consts::FRAC_1_SQRT_2);
+        assert_close(samples[0].powi(2) + samples[1].powi(2), 1.0);
+    }
+
+    #[test]
+    fn hard_left_and_right() {
+        let samples: Vec<f32> = SamplesBuffer::new(1, 44100, vec![1.0f32])
+            .pan(-1.0)
+            .collect();
+        assert_close(samples[0], 1.0);
+        assert_close(samples[1], 0.0);
+
+        let samples: Vec<f32> = SamplesBuffer::new(1, 44100, vec![1.0f32])
+            .pan(5.0)
+            .collect();
+        assert_close(samples[0], 0.0);
+        assert_close(samples[1], 1.0);
+    }
+
+    #[test]
+    fn stereo_is_mixed_down_and_repanned() {
+        let source = SamplesBuffer::new(2, 44100, vec![1.0f32, 0.0]).pan(1.0);
+        let samples: Vec<f32> = source.collect();
+        assert_close(samples[0], 0.0);
+        assert_close(samples[1], 0.5);
+    }
+
+    #[test]
+    fn position_changes_while_playing() {
+        let mut source = SamplesBuffer::new(1, 44100, vec![1.0f32; 2]).pan(-1.0);
+        let position = source.position();
+        assert_close(source.next().unwrap(), 1.0);
+        assert_close(source.next().unwrap(), 0.0);
+
+        position.set(1.0);
+        assert_eq!(position.get(), 1.0);
+        assert_close(source.next().unwrap(), 0.0);
+        assert_close(source.next().unwrap(), 1.0);
+        assert_eq!(source.next(), None);
+    }
+}
@@ -120,10 +120,55 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
+    /// Passes the samples between two calls of the closure through at once.
+    fn fill_buffer(&mut self, out: &mut [I::Item]) -> usize {
+        let mut filled = 0;
+        while filled < out.len() {
+            if self.samples_until_update == 1 {
+                // due before the next sample, as in `next`
+                (self.modifier)(&mut self.input);
+                self.samples_until_update = self.update_frequency + 1;
+            }
+            let len = ((self.samples_until_update - 1) as usize).min(out.len() - filled);
+            let written = self.input.fill_buffer(&mut out[filled..filled + len]);
+            self.samples_until_update -= written as u32;
+            filled += written;
+            if written < len {
+                break;
+            }
+        }
+        filled
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
 }
 
 #[cfg(test)]
@@ -171,4 +216,35 @@ mod tests {
         source.next();
         source.next(); // Would overflow here.
     }
+
+    #[test]
+    fn fill_buffer_calls_at_the_same_samples() {
+        let make = || {
+            let inner = SamplesBuffer::new(1, 1000, (0..9).map(|n| n as f32).collect::<Vec<_>>());
+            let mut calls = 0.0;
+            inner
+                .amplify(1.0)
+                .periodic_access(Duration::from_millis(2), move |src| {
+                    calls += 1.0;
+                    src.set_factor(calls);
+                })
+        };
+        let expected: Vec<f32> = make().collect();
+
+        let mut source = make();
+        let mut filled = Vec::new();
+        let mut chunk = [0.0; 3];
+        loop {
+            let len = source.fill_buffer(&mut chunk);
+            filled.extend_from_slice(&chunk[..len]);
+            if len < chunk.len() {
+                break;
+            }
+        }
+        assert_eq!(
+            expected,
+            vec![0.0, 1.0, 4.0, 6.0, 12.0, 15.0, 24.0, 28.0, 40.0]
+        );
+        assert_eq!(filled, expected);
+    }
 }
@@ -144,17 +144,41 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        let result = self.input.try_seek(pos);
-        if result.is_ok() {
-            self.offset_duration = pos.as_secs_f64();
-            // This assumes that the seek implementation of the codec always
-            // starts again at the beginning of a frame. Which is the case with
-            // symphonia.
-            self.samples_counted = 0;
-        }
-        result
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    /// The position starts counting again from where the input landed.
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.input.try_seek_exact(pos)?;
+        self.offset_duration = pos.as_secs_f64();
+        // This assumes that the seek implementation of the codec always
+        // starts again at the beginning of a frame. Which is the case with
+        // symphonia.
+        self.samples_counted = 0;
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
     }
 }
 
@@ -196,4 +220,22 @@ mod tests {
         assert_eq!(source.try_seek(Duration::new(1, 0)).is_ok(), true);
         assert_eq!(source.get_pos().as_secs_f32(), 1.0);
     }
+
+    #[test]
+    fn try_seek_exact_reports_the_landed_position() {
+        let mut source = SamplesBuffer::new(2, 1000, vec![0i16; 200])
+            .track_position()
+            .amplify(1.0)
+            .pausable(false)
+            .skippable()
+            .stoppable()
+            .periodic_access(Duration::from_millis(5), |_| {});
+        // the buffer lands on whole frames
+        let reached = source
+            .try_seek_exact(Duration::from_micros(20_250))
+            .unwrap();
+        assert_eq!(reached, Duration::from_millis(20));
+        let position = source.inner().inner().inner().inner().inner().get_pos();
+        assert_eq!(position, Duration::from_millis(20));
+    }
 }
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Rechunk` object.
+pub fn rechunk<I>(input: I, frames_per_block: usize) -> Rechunk<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(
+        frames_per_block > 0,
+        "rechunk needs at least one frame per block"
+    );
+    Rechunk {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        input,
+        frames_per_block,
+        block: Vec::new(),
+        position: 0,
+        pending: None,
+        span_left: Some(0),
+    }
+}
+
+/// Re-emits a source in blocks of a fixed number of frames, for consumers such as encoders
+/// and FFT based effects that work on fixed size blocks.
+///
+/// [`current_frame_len`](Source::current_frame_len) reports the samples left in the current
+/// block, every block has the same channel count and sample rate throughout. The last block,
+/// and a block during which the input changes its channel count or sample rate, is padded
+/// with silence to the full block size. No other samples are added or dropped.
+#[derive(Clone, Debug)]
+pub struct Rechunk<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    frames_per_block: usize,
+    // The current block and the position of the next sample in it.
+    block: Vec<I::Item>,
+    position: usize,
+    // A sample read to find out the format of a new span, that starts the next block.
+    pending: Option<I::Item>,
+    // The samples left in the current span of the input, the next sample starts a new span
+    // at zero.
+    span_left: Option<usize>,
+    // The format of the current block.
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl<I> Rechunk<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the number of frames in every block.
+    #[inline]
+    pub fn frames_per_block(&self) -> usize {
+        self.frames_per_block
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    #[inline]
+    fn block_len(&self, channels: u16) -> usize {
+        self.frames_per_block * channels.max(1) as usize
+    }
+
+    /// Reads a sample from the input, and whether it starts a new span.
+    #[inline]
+    fn next_input(&mut self) -> Option<(I::Item, bool)> {
+        let sample = self.input.next()?;
+        let new_span = match self.span_left {
+            Some(0) => {
+                self.span_left = self.input.current_frame_len();
+                true
+            }
+            Some(left) => {
+                self.span_left = Some(left - 1);
+                false
+            }
+            None => false,
+        };
+        Some((sample, new_span))
+    }
+
+    /// Reads the next block from the input, returns false once the input ended.
+    fn next_block(&mut self) -> bool {
+        self.block.clear();
+        self.position = 0;
+        let first = match self.pending.take() {
+            Some(sample) => sample,
+            None => match self.next_input() {
+                Some((sample, _)) => sample,
+                None => return false,
+            },
+        };
+        self.channels = self.input.channels();
+        self.sample_rate = self.input.sample_rate();
+        let len = self.block_len(self.channels);
+        self.block.push(first);
+
+        while self.block.len() < len {
+            let Some((sample, new_span)) = self.next_input() else {
+                break;
+            };
+            // a span with another format starts a new block
+            if new_span
+                && (self.input.channels() != self.channels
+                    || self.input.sample_rate() != self.sample_rate)
+            {
+                self.pending = Some(sample);
+                break;
+            }
+            self.block.push(sample);
+        }
+
+        self.block.resize(len, I::Item::zero_value());
+        true
+    }
+}
+
+impl<I> Iterator for Rechunk<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.position == self.block.len() && !self.next_block() {
+            return None;
+        }
+        let sample = self.block[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left_in_block = self.block.len() - self.position + self.pending.is_some() as usize;
+        let (min, max) = self.input.size_hint();
+        // the padding of blocks cut short by a format change is not known in advance
+        let max = match self.input.current_frame_len() {
+            None => max.and_then(|max| {
+                let len = self.block_len(self.input.channels());
+                max.div_ceil(len)
+                    .checked_mul(len)?
+                    .checked_add(left_in_block)
+            }),
+            Some(_) => None,
+        };
+        (min.saturating_add(left_in_block), max)
+    }
+}
+
+impl<I> Source for Rechunk<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.position < self.block.len() {
+            return Some(self.block.len() - self.position);
+        }
+        Some(self.block_len(self.input.channels()))
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.position < self.block.len() {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.position < self.block.len() {
+            self.sample_rate
+        } else {
+            self.input.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.input.try_seek_exact(pos)?;
+        self.block.clear();
+        self.position = 0;
+        self.pending = None;
+        self.span_left = Some(0);
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{from_iter, Source};
+
+    #[test]
+    fn fixed_blocks_with_padded_end() {
+        let mut source = SamplesBuffer::new(2, 48000, (1i16..=10).collect::<Vec<_>>()).rechunk(2);
+        assert_eq!(source.current_frame_len(), Some(4));
+        assert_eq!(source.size_hint(), (10, Some(12)));
+
+        let mut blocks = Vec::new();
+        while let Some(first) = source.next() {
+            assert_eq!(source.current_frame_len(), Some(3));
+            assert_eq!(source.channels(), 2);
+            let mut block = vec![first];
+            block.extend(source.by_ref().take(3));
+            blocks.push(block);
+        }
+        assert_eq!(
+            blocks,
+            vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 0, 0]]
+        );
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn format_change_starts_a_new_block() {
+        let spans = vec![
+            SamplesBuffer::new(1, 48000, vec![1i16, 2, 3]),
+            SamplesBuffer::new(2, 44100, vec![4i16, 5, 6, 7]),
+        ];
+        let mut source = from_iter(spans).rechunk(2);
+
+        let first: Vec<i16> = source.by_ref().take(4).collect();
+        assert_eq!(first, vec![1, 2, 3, 0]);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), 44100);
+        assert_eq!(source.current_frame_len(), Some(4));
+        assert_eq!(source.collect::<Vec<_>>(), vec![4, 5, 6, 7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_frames_per_block() {
+        SamplesBuffer::new(1, 48000, vec![1i16]).rechunk(0);
+    }
+}
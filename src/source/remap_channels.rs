@@ -0,0 +1,342 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `RemapChannels` object.
+pub fn remap_channels<I>(input: I, map: Vec<Option<u16>>) -> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    build(input, map, None).unwrap_or_else(|err| panic!("remap_channels: {err}"))
+}
+
+/// Internal function that builds a `RemapChannels` object for an output with
+/// `output_channels` channels.
+pub fn try_remap_channels<I>(
+    input: I,
+    map: Vec<Option<u16>>,
+    output_channels: u16,
+) -> Result<RemapChannels<I>, RemapError>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    build(input, map, Some(output_channels))
+}
+
+fn build<I>(
+    input: I,
+    map: Vec<Option<u16>>,
+    output_channels: Option<u16>,
+) -> Result<RemapChannels<I>, RemapError>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    if map.is_empty() {
+        return Err(RemapError::Empty);
+    }
+    let Ok(channels) = u16::try_from(map.len()) else {
+        return Err(RemapError::TooManyChannels(map.len()));
+    };
+    if let Some(expected) = output_channels.filter(|&expected| expected != channels) {
+        return Err(RemapError::ChannelMismatch { channels, expected });
+    }
+    let input_channels = input.channels();
+    if let Some(&index) = map.iter().flatten().find(|&&index| index >= input_channels) {
+        return Err(RemapError::MissingInputChannel {
+            index,
+            input_channels,
+        });
+    }
+
+    Ok(RemapChannels {
+        input,
+        frame: Vec::with_capacity(input_channels as usize),
+        map,
+        output_channel: 0,
+    })
+}
+
+/// Occurs when a channel map passed to [`Source::try_remap_channels`] or
+/// [`Source::remap_channels`] can not be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum RemapError {
+    /// The map has no output channels.
+    #[error("the map needs at least one output channel")]
+    Empty,
+    /// The map has more output channels than a source can have.
+    #[error("the map has {0} output channels, at most {max} are supported", max = u16::MAX)]
+    TooManyChannels(usize),
+    /// The map has another number of output channels than the output it is meant for.
+    #[error("the map has {channels} output channels but the output has {expected}")]
+    ChannelMismatch {
+        /// The number of output channels of the map.
+        channels: u16,
+        /// The number of channels of the output.
+        expected: u16,
+    },
+    /// The map refers to a channel the input does not have.
+    #[error("input channel {index} does not exist, the input has {input_channels}")]
+    MissingInputChannel {
+        /// The input channel in the map.
+        index: u16,
+        /// The number of channels of the input.
+        input_channels: u16,
+    },
+}
+
+/// Routes the channels of the input to chosen channels of a source with another number of
+/// channels, for example to play a mono sound on the center speaker of a 5.1 system.
+///
+/// Output channels that are not mapped to an input channel are silent. An input channel can
+/// feed more than one output channel.
+#[derive(Clone, Debug)]
+pub struct RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // For every output channel the input channel it plays.
+    map: Vec<Option<u16>>,
+    // The current input frame.
+    frame: Vec<I::Item>,
+    // The output channel of the next sample.
+    output_channel: usize,
+}
+
+impl<I> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns for every output channel the input channel it plays, `None` for silence.
+    #[inline]
+    pub fn map(&self) -> &[Option<u16>] {
+        &self.map
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads the next frame of the input, returns false once the input ended.
+    fn next_frame(&mut self) -> bool {
+        self.frame.clear();
+        let channels = self.input.channels();
+        for _ in 0..channels {
+            match self.input.next() {
+                Some(sample) => self.frame.push(sample),
+                None => break,
+            }
+        }
+        if self.frame.is_empty() {
+            return false;
+        }
+        // an incomplete last frame is padded with silence
+        self.frame.resize(channels as usize, I::Item::zero_value());
+        true
+    }
+}
+
+impl<I> Iterator for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.output_channel == 0 && !self.next_frame() {
+            return None;
+        }
+
+        let sample = self.map[self.output_channel]
+            // the input may have fewer channels after a frame boundary
+            .and_then(|index| self.frame.get(index as usize).copied())
+            .unwrap_or_else(I::Item::zero_value);
+        self.output_channel = (self.output_channel + 1) % self.map.len();
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let input_channels = self.input.channels().max(1) as usize;
+        let output_channels = self.map.len();
+        let in_frame = if self.output_channel == 0 {
+            0
+        } else {
+            output_channels - self.output_channel
+        };
+        let (min, max) = self.input.size_hint();
+        let min = (min / input_channels)
+            .saturating_mul(output_channels)
+            .saturating_add(in_frame);
+        let max = max.and_then(|max| {
+            max.div_ceil(input_channels)
+                .checked_mul(output_channels)?
+                .checked_add(in_frame)
+        });
+        (min, max)
+    }
+}
+
+impl<I> Source for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.output_channel != 0 {
+            // the frame boundary of the input is after the current output frame
+            return Some(self.map.len() - self.output_channel);
+        }
+        let input_channels = self.input.channels().max(1) as usize;
+        self.input
+            .current_frame_len()
+            .map(|len| len.div_ceil(input_channels) * self.map.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.map.len() as u16
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    /// The frames of the input, an incomplete last one included, times the output channels.
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        let input_channels = self.input.channels().max(1) as u64;
+        let frames = self.input.total_len()?.div_ceil(input_channels);
+        frames.checked_mul(self.map.len() as u64)
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.try_seek_exact(pos).map(|_| ())
+    }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let pos = self.input.try_seek_exact(pos)?;
+        self.output_channel = 0;
+        Ok(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{RemapError, Source};
+
+    #[test]
+    fn mono_to_center_of_surround() {
+        let source = SamplesBuffer::new(1, 48000, vec![1i16, 2]);
+        let source = source.remap_channels(vec![None, None, Some(0), None, None, None]);
+        assert_eq!(source.channels(), 6);
+        assert_eq!(source.size_hint(), (12, Some(12)));
+        assert_eq!(source.total_len(), Some(12));
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            vec![0, 0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn swaps_and_duplicates_channels() {
+        let source = SamplesBuffer::new(2, 48000, vec![1i16, 2, 3, 4]);
+        let source = source.remap_channels(vec![Some(1), Some(0), Some(0)]);
+        assert_eq!(source.collect::<Vec<_>>(), vec![2, 1, 1, 4, 3, 3]);
+    }
+
+    #[test]
+    fn frame_len_stays_aligned() {
+        let mut source = SamplesBuffer::new(2, 48000, vec![1i16, 2, 3, 4])
+            .fit_to_duration(std::time::Duration::from_nanos(41667))
+            .remap_channels(vec![Some(0), None, Some(1)]);
+        assert_eq!(source.current_frame_len(), Some(6));
+        source.next();
+        assert_eq!(source.current_frame_len(), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_input_channel() {
+        SamplesBuffer::new(2, 48000, vec![1i16, 2]).remap_channels(vec![Some(2)]);
+    }
+
+    #[test]
+    fn checked_against_the_output() {
+        let source = || SamplesBuffer::new(1, 48000, vec![1i16, 2]);
+        let center = vec![None, None, Some(0), None, None, None];
+        assert!(source().try_remap_channels(center.clone(), 6).is_ok());
+        assert_eq!(
+            source().try_remap_channels(center, 2).unwrap_err(),
+            RemapError::ChannelMismatch {
+                channels: 6,
+                expected: 2
+            }
+        );
+        assert_eq!(
+            source().try_remap_channels(vec![Some(1)], 1).unwrap_err(),
+            RemapError::MissingInputChannel {
+                index: 1,
+                input_channels: 1
+            }
+        );
+        assert_eq!(
+            source().try_remap_channels(Vec::new(), 0).unwrap_err(),
+            RemapError::Empty
+        );
+        assert_eq!(
+            source()
+                .try_remap_channels(vec![None; u16::MAX as usize + 1], u16::MAX)
+                .unwrap_err(),
+            RemapError::TooManyChannels(u16::MAX as usize + 1)
+        );
+    }
+}
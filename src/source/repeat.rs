@@ -91,6 +91,26 @@ where
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.inner.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.inner.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.inner.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.inner.label()
+    }
 }
 
 impl<I> Clone for Repeat<I>
@@ -106,3 +126,27 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn total_duration_is_unknown() {
+        let source = SamplesBuffer::new(1, 1000, vec![1i16, 2]).repeat_infinite();
+        assert_eq!(source.total_duration(), None);
+        assert_eq!(source.take(5).collect::<Vec<_>>(), vec![1, 2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn forwards_the_input() {
+        let mut source = SamplesBuffer::new(1, 1000, vec![1i16, 2])
+            .labeled("loop")
+            .repeat_infinite();
+        assert_eq!(source.label(), Some("loop"));
+        // the input is buffered, which can not seek
+        assert!(!source.is_seekable());
+        assert!(source.try_seek(std::time::Duration::ZERO).is_err());
+    }
+}
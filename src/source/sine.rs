@@ -66,4 +66,9 @@ impl Source for SineWave {
         // of seeking
         Ok(())
     }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
 }
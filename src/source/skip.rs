@@ -12,24 +12,26 @@ where
     I: Source,
     I::Item: Sample,
 {
-    do_skip_duration(&mut input, duration);
+    let skipped_samples = do_skip_duration(&mut input, duration);
     SkipDuration {
         input,
         skipped_duration: duration,
+        skipped_samples,
     }
 }
 
 /// Skips specified `duration` of the given `input` source from it's current position.
-fn do_skip_duration<I>(input: &mut I, mut duration: Duration)
+/// Returns the number of samples skipped.
+fn do_skip_duration<I>(input: &mut I, mut duration: Duration) -> u64
 where
     I: Source,
     I::Item: Sample,
 {
+    let mut skipped = 0;
     while duration > Duration::new(0, 0) {
         if input.current_frame_len().is_none() {
             // Sample rate and the amount of channels will be the same till the end.
-            do_skip_duration_unchecked(input, duration);
-            return;
+            return skipped + do_skip_duration_unchecked(input, duration);
         }
 
         // .unwrap() safety: if `current_frame_len()` is None, the body of the `if` statement
@@ -38,7 +40,7 @@ where
         // If frame_len is zero, then there is no more data to skip. Instead
         // just bail out.
         if frame_len == 0 {
-            return;
+            return skipped;
         }
 
         let ns_per_sample: u128 =
@@ -46,19 +48,19 @@ where
 
         // Check if we need to skip only part of the current frame.
         if frame_len as u128 * ns_per_sample > duration.as_nanos() {
-            skip_samples(input, (duration.as_nanos() / ns_per_sample) as usize);
-            return;
+            return skipped + skip_samples(input, (duration.as_nanos() / ns_per_sample) as usize);
         }
 
-        skip_samples(input, frame_len);
+        skipped += skip_samples(input, frame_len);
 
         duration -= Duration::from_nanos((frame_len * ns_per_sample as usize) as u64);
     }
+    skipped
 }
 
 /// Skips specified `duration` from the `input` source assuming that sample rate
 /// and amount of channels are not changing.
-fn do_skip_duration_unchecked<I>(input: &mut I, duration: Duration)
+fn do_skip_duration_unchecked<I>(input: &mut I, duration: Duration) -> u64
 where
     I: Source,
     I::Item: Sample,
@@ -67,20 +69,24 @@ where
         duration.as_nanos() * input.sample_rate() as u128 / NS_PER_SECOND;
     let samples_to_skip: u128 = samples_per_channel * input.channels() as u128;
 
-    skip_samples(input, samples_to_skip as usize);
+    skip_samples(input, samples_to_skip as usize)
 }
 
-/// Skips `n` samples from the given `input` source.
-fn skip_samples<I>(input: &mut I, n: usize)
+/// Skips `n` samples from the given `input` source. Returns the number of samples skipped,
+/// fewer than `n` if the input ended.
+fn skip_samples<I>(input: &mut I, n: usize) -> u64
 where
     I: Source,
     I::Item: Sample,
 {
+    let mut skipped = 0;
     for _ in 0..n {
         if input.next().is_none() {
             break;
         }
+        skipped += 1;
     }
+    skipped
 }
 
 /// A source that skips specified duration of the given source from it's current position.
@@ -88,6 +94,7 @@ where
 pub struct SkipDuration<I> {
     input: I,
     skipped_duration: Duration,
+    skipped_samples: u64,
 }
 
 impl<I> SkipDuration<I>
@@ -160,10 +167,36 @@ where
         })
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        let len = self.input.total_len()?;
+        Some(len.saturating_sub(self.skipped_samples))
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
 }
 
 #[cfg(test)]
@@ -186,9 +219,13 @@ mod tests {
         let seconds_left = seconds.saturating_sub(seconds_to_skip);
 
         let samples_left_expected = (sample_rate * channels as u32 * seconds_left) as usize;
-        let samples_left = test_buffer
-            .skip_duration(Duration::from_secs(seconds_to_skip as u64))
-            .count();
+        let source = test_buffer.skip_duration(Duration::from_secs(seconds_to_skip as u64));
+        assert_eq!(
+            source.total_duration(),
+            Some(Duration::from_secs(seconds_left as u64))
+        );
+        assert_eq!(source.total_len(), Some(samples_left_expected as u64));
+        let samples_left = source.count();
 
         assert_eq!(samples_left, samples_left_expected);
     }
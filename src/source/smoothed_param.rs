@@ -0,0 +1,303 @@
+use std::time::Duration;
+
+/// How a [`SmoothedParam`] moves towards its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmoothingMode {
+    /// Moves in a straight line and reaches the target after exactly the smoothing time.
+    Linear,
+    /// Closes a fixed fraction of the remaining distance every step, like an RC filter. The
+    /// smoothing time is the time constant: after it has passed about 63% of the distance
+    /// to the target has been covered.
+    OnePole,
+}
+
+// A one pole glide is considered done once it is this close to its target.
+const ONE_POLE_SNAP: f32 = 1e-6;
+
+/// A parameter, like a volume or a filter cutoff, that glides to new values instead of
+/// jumping to them. Jumps in parameters cause audible clicks.
+///
+/// The parameter advances one step every time [`next_value`](SmoothedParam::next_value) is
+/// called. The number of steps per second is given by the update rate, usually the sample
+/// rate or the frames per second of the source the parameter belongs to.
+#[derive(Clone, Debug)]
+pub struct SmoothedParam {
+    value: f32,
+    target: f32,
+    mode: SmoothingMode,
+    time: Duration,
+    update_rate: u32,
+    // Linear: the change per step and the steps left.
+    step: f32,
+    steps_left: u64,
+    // One pole: the fraction of the distance covered each step.
+    coefficient: f32,
+}
+
+impl SmoothedParam {
+    /// Builds a parameter at `value` that glides to new targets over `time`, advancing
+    /// `update_rate` steps per second.
+    pub fn new(value: f32, mode: SmoothingMode, time: Duration, update_rate: u32) -> Self {
+        let mut param = SmoothedParam {
+            value,
+            target: value,
+            mode,
+            time,
+            update_rate,
+            step: 0.0,
+            steps_left: 0,
+            coefficient: 1.0,
+        };
+        param.update_coefficient();
+        param
+    }
+
+    /// Builds a parameter that glides linearly, see [`SmoothingMode::Linear`].
+    pub fn linear(value: f32, time: Duration, update_rate: u32) -> Self {
+        SmoothedParam::new(value, SmoothingMode::Linear, time, update_rate)
+    }
+
+    /// Builds a parameter that glides exponentially, see [`SmoothingMode::OnePole`].
+    pub fn one_pole(value: f32, time_constant: Duration, update_rate: u32) -> Self {
+        SmoothedParam::new(value, SmoothingMode::OnePole, time_constant, update_rate)
+    }
+
+    /// Starts gliding from the current value to `target`.
+    ///
+    /// Setting the target the parameter is already gliding to does nothing, so the glide in
+    /// progress is not restarted.
+    pub fn set_target(&mut self, target: f32) {
+        if target == self.target {
+            return;
+        }
+        self.target = target;
+        self.start_glide();
+    }
+
+    /// Jumps to `value` right away, stopping any glide.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value;
+        self.target = value;
+        self.steps_left = 0;
+    }
+
+    /// Changes how long a glide takes. A glide in progress restarts from where it is, taking
+    /// the new time to cover the remaining distance.
+    pub fn set_time(&mut self, time: Duration) {
+        self.time = time;
+        self.update_coefficient();
+        self.start_glide();
+    }
+
+    /// Changes the number of steps per second. A glide in progress restarts from where it
+    /// is, taking the full glide time to cover the remaining distance.
+    pub fn set_update_rate(&mut self, update_rate: u32) {
+        if update_rate == self.update_rate {
+            return;
+        }
+        self.update_rate = update_rate;
+        self.update_coefficient();
+        self.start_glide();
+    }
+
+    /// Advances the glide one step and returns the new value.
+    #[inline]
+    pub fn next_value(&mut self) -> f32 {
+        if self.is_smoothing() {
+            match self.mode {
+                SmoothingMode::Linear => {
+                    self.steps_left -= 1;
+                    self.value = if self.steps_left == 0 {
+                        self.target
+                    } else {
+                        self.value + self.step
+                    };
+                }
+                SmoothingMode::OnePole => {
+                    let value = self.value + (self.target - self.value) * self.coefficient;
+                    // close to the target the steps get too small for an f32 to represent
+                    self.value =
+                        if value == self.value || (self.target - value).abs() <= ONE_POLE_SNAP {
+                            self.target
+                        } else {
+                            value
+                        };
+                }
+            }
+        }
+        self.value
+    }
+
+    /// Returns the current value, without advancing the glide.
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Returns the value being glided to.
+    #[inline]
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Returns true while the value has not reached the target yet.
+    #[inline]
+    pub fn is_smoothing(&self) -> bool {
+        match self.mode {
+            SmoothingMode::Linear => self.steps_left > 0,
+            SmoothingMode::OnePole => self.value != self.target,
+        }
+    }
+
+    fn steps(&self) -> u64 {
+        (self.time.as_secs_f64() * self.update_rate as f64).round() as u64
+    }
+
+    fn update_coefficient(&mut self) {
+        let steps = self.time.as_secs_f64() * self.update_rate as f64;
+        self.coefficient = if steps > 0.0 {
+            (1.0 - (-1.0 / steps).exp()) as f32
+        } else {
+            1.0
+        };
+    }
+
+    fn start_glide(&mut self) {
+        if self.value == self.target {
+            self.steps_left = 0;
+            return;
+        }
+        if self.mode == SmoothingMode::OnePole {
+            if self.steps() == 0 {
+                self.value = self.target;
+            }
+            return;
+        }
+        self.steps_left = self.steps();
+        if self.steps_left == 0 {
+            self.value = self.target;
+        } else {
+            self.step = (self.target - self.value) / self.steps_left as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{SmoothedParam, SmoothingMode};
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn linear_reaches_target_in_time() {
+        let mut param = SmoothedParam::linear(0.0, Duration::from_millis(10), 1000);
+        param.set_target(1.0);
+        assert!(param.is_smoothing());
+        assert_eq!(param.value(), 0.0);
+
+        for step in 1..10 {
+            assert_close(param.next_value(), step as f32 / 10.0);
+            assert!(param.is_smoothing());
+        }
+        assert_eq!(param.next_value(), 1.0);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.next_value(), 1.0);
+    }
+
+    #[test]
+    fn linear_retarget_starts_from_current_value() {
+        let mut param = SmoothedParam::linear(0.0, Duration::from_millis(4), 1000);
+        param.set_target(1.0);
+        param.next_value();
+        param.next_value();
+        assert_close(param.value(), 0.5);
+
+        param.set_target(-0.5);
+        for expected in [0.25, 0.0, -0.25, -0.5] {
+            assert_close(param.next_value(), expected);
+        }
+        assert!(!param.is_smoothing());
+    }
+
+    #[test]
+    fn same_target_does_not_restart() {
+        let mut param = SmoothedParam::linear(0.0, Duration::from_millis(4), 1000);
+        param.set_target(1.0);
+        param.next_value();
+        param.set_target(1.0);
+        param.next_value();
+        param.set_target(1.0);
+        assert_close(param.value(), 0.5);
+        param.next_value();
+        param.next_value();
+        assert_eq!(param.value(), 1.0);
+        assert!(!param.is_smoothing());
+    }
+
+    #[test]
+    fn one_pole_time_constant() {
+        let rate = 48000;
+        let mut param = SmoothedParam::one_pole(0.0, Duration::from_millis(10), rate);
+        param.set_target(1.0);
+        for _ in 0..480 {
+            param.next_value();
+        }
+        // one time constant covers 1 - 1/e of the distance
+        assert_close(param.value(), 1.0 - (-1.0f32).exp());
+
+        for _ in 0..480 * 4 {
+            param.next_value();
+        }
+        assert_close(param.value(), 1.0 - (-5.0f32).exp());
+
+        // it does eventually settle exactly on the target
+        for _ in 0..480 * 20 {
+            param.next_value();
+        }
+        assert_eq!(param.value(), 1.0);
+        assert!(!param.is_smoothing());
+    }
+
+    #[test]
+    fn zero_time_jumps() {
+        for mode in [SmoothingMode::Linear, SmoothingMode::OnePole] {
+            let mut param = SmoothedParam::new(0.0, mode, Duration::ZERO, 1000);
+            param.set_target(2.0);
+            assert_eq!(param.value(), 2.0);
+            assert!(!param.is_smoothing());
+        }
+    }
+
+    #[test]
+    fn set_value_stops_glide() {
+        let mut param = SmoothedParam::linear(0.0, Duration::from_millis(10), 1000);
+        param.set_target(1.0);
+        param.next_value();
+        param.set_value(0.3);
+        assert!(!param.is_smoothing());
+        assert_eq!(param.target(), 0.3);
+        assert_eq!(param.next_value(), 0.3);
+    }
+
+    #[test]
+    fn update_rate_change_keeps_glide_duration() {
+        let mut param = SmoothedParam::linear(0.0, Duration::from_millis(10), 1000);
+        param.set_target(1.0);
+        for _ in 0..5 {
+            param.next_value();
+        }
+        // the remaining distance is now covered over 10ms at the new rate
+        param.set_update_rate(2000);
+        let mut steps = 0;
+        while param.is_smoothing() {
+            param.next_value();
+            steps += 1;
+        }
+        assert_eq!(steps, 20);
+        assert_eq!(param.value(), 1.0);
+    }
+}
@@ -1,8 +1,6 @@
 use std::time::Duration;
 
-use interpolation;
-
-use crate::source::ChannelVolume;
+use crate::source::{ChannelVolume, SmoothedParam};
 use crate::{Sample, Source};
 
 use super::SeekError;
@@ -16,10 +14,14 @@ where
     I: Source,
     I::Item: Sample,
 {
-    lerp_position: f32,
-    left_start: f32,
-    right_start: f32,
     input: ChannelVolume<I>,
+    // Volumes of the left and right channel, advanced once per frame.
+    volumes: [SmoothedParam; 2],
+    distance_model: DistanceModel,
+    // The last positions of the emitter, left and right ear.
+    positions: [[f32; 3]; 3],
+    // The output channel of the next sample.
+    channel: u16,
 }
 
 fn dist_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
@@ -29,6 +31,73 @@ fn dist_sq(a: [f32; 3], b: [f32; 3]) -> f32 {
         .sum::<f32>()
 }
 
+/// How the volume of a [`Spatial`] source falls off with the distance between the source
+/// and an ear, using the reference distance, maximum distance and rolloff factor that game
+/// engines like FMOD, Wwise and Unity use.
+///
+/// Up to the reference distance the sound plays at full volume. Further away the volume is
+/// `(distance / reference).powf(-rolloff)`, the "exponent distance clamped" model of OpenAL.
+/// A rolloff of `1.0` halves the volume, -6 dB, every time the distance doubles, like the
+/// inverse or logarithmic rolloff of most engines. Beyond the maximum distance the volume
+/// stays at what it is at the maximum distance.
+///
+/// The default is a reference distance of `1.0`, no maximum distance and a rolloff of `2.0`:
+/// the volume falls with the square of the distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DistanceModel {
+    /// The distance up to which the sound is at full volume. Must be above zero.
+    pub reference: f32,
+    /// The distance beyond which the sound gets no quieter, `f32::INFINITY` for none.
+    pub max: f32,
+    /// How fast the volume falls off beyond the reference distance, `0.0` for not at all.
+    pub rolloff: f32,
+}
+
+impl Default for DistanceModel {
+    fn default() -> Self {
+        DistanceModel {
+            reference: 1.0,
+            max: f32::INFINITY,
+            rolloff: 2.0,
+        }
+    }
+}
+
+impl DistanceModel {
+    /// Returns the volume, between `0.0` and `1.0`, of a sound at `distance`.
+    pub fn gain(&self, distance: f32) -> f32 {
+        let reference = self.reference.max(f32::MIN_POSITIVE);
+        let distance = distance.min(self.max).max(reference);
+        (distance / reference).powf(-self.rolloff).min(1.0)
+    }
+}
+
+/// Volumes of the left and right ear for a sound at `emitter_pos`.
+fn ear_volumes(
+    emitter_pos: [f32; 3],
+    left_ear: [f32; 3],
+    right_ear: [f32; 3],
+    model: &DistanceModel,
+) -> [f32; 2] {
+    debug_assert!(left_ear != right_ear);
+    let left_dist_sq = dist_sq(left_ear, emitter_pos);
+    let right_dist_sq = dist_sq(right_ear, emitter_pos);
+    let max_diff = dist_sq(left_ear, right_ear).sqrt();
+    let left_dist = left_dist_sq.sqrt();
+    let right_dist = right_dist_sq.sqrt();
+
+    let left_diff_modifier = (((left_dist - right_dist) / max_diff + 1.0) / 4.0 + 0.5).min(1.0);
+    let right_diff_modifier = (((right_dist - left_dist) / max_diff + 1.0) / 4.0 + 0.5).min(1.0);
+
+    let left_dist_modifier = model.gain(left_dist);
+    let right_dist_modifier = model.gain(right_dist);
+
+    [
+        left_diff_modifier * left_dist_modifier,
+        right_diff_modifier * right_dist_modifier,
+    ]
+}
+
 impl<I> Spatial<I>
 where
     I: Source,
@@ -45,56 +114,117 @@ where
         I: Source,
         I::Item: Sample,
     {
+        let frame_rate = input.sample_rate();
+        let volume = SmoothedParam::linear(0.0, Duration::ZERO, frame_rate);
         let mut ret = Spatial {
-            lerp_position: 0.0,
-            left_start: 0.0,
-            right_start: 0.0,
             input: ChannelVolume::new(input, vec![0.0, 0.0]),
+            volumes: [volume.clone(), volume],
+            distance_model: DistanceModel::default(),
+            positions: [emitter_position, left_ear, right_ear],
+            channel: 0,
         };
         ret.set_positions(emitter_position, left_ear, right_ear);
         ret
     }
 
+    /// Restarts the current glide from the volumes that are playing right now.
+    #[deprecated(
+        since = "0.20.0",
+        note = "`set_positions_smooth` starts a new glide whenever the target changes"
+    )]
     pub fn reset_lerp(&mut self) {
-        self.lerp_position = 0.0;
-        self.left_start = self.input.get_volume(0);
-        self.right_start = self.input.get_volume(1);
+        for volume in &mut self.volumes {
+            let target = volume.target();
+            volume.set_value(volume.value());
+            volume.set_target(target);
+        }
     }
 
-    /// Sets the position of the emitter and ears in the 3D world.
+    /// Sets the position of the emitter and ears in the 3D world. The volumes change right
+    /// away, see [`set_positions_smooth`](Spatial::set_positions_smooth) to avoid clicks.
     pub fn set_positions(
         &mut self,
         emitter_pos: [f32; 3],
         left_ear: [f32; 3],
         right_ear: [f32; 3],
     ) {
-        debug_assert!(left_ear != right_ear);
-        let left_dist_sq = dist_sq(left_ear, emitter_pos);
-        let right_dist_sq = dist_sq(right_ear, emitter_pos);
-        let max_diff = dist_sq(left_ear, right_ear).sqrt();
-        let left_dist = left_dist_sq.sqrt();
-        let right_dist = right_dist_sq.sqrt();
-
-        let left_diff_modifier =  (((left_dist - right_dist) / max_diff + 1.0) / 4.0 + 0.5).min(1.0);
-        let right_diff_modifier = (((right_dist - left_dist) / max_diff + 1.0) / 4.0 + 0.5).min(1.0);
-
-        let left_dist_modifier = (1.0 / left_dist_sq).min(1.0);
-        let right_dist_modifier = (1.0 / right_dist_sq).min(1.0);
-
-        let left_target = left_diff_modifier * left_dist_modifier;
-        let right_target = right_diff_modifier * right_dist_modifier;
-
-        // lerp to the new target volume.  Lerping helps to smooth out 
-        // volume changes to avoid high frequency clicks during position changes.
-        let new_left_vol = interpolation::lerp(&self.left_start, &left_target, &self.lerp_position);
-        let new_right_vol = interpolation::lerp(&self.right_start, &right_target, &self.lerp_position);
-        self.lerp_position = self.lerp_position + 0.125;
-        self.lerp_position = self.lerp_position.clamp(0.0, 1.0);
-
-        self.input
-            .set_volume(0, new_left_vol);
-        self.input
-            .set_volume(1, new_right_vol);
+        self.set_positions_smooth(emitter_pos, left_ear, right_ear, Duration::ZERO);
+    }
+
+    /// Sets the position of the emitter and ears in the 3D world, gliding from the current
+    /// volumes to the new ones over `smoothing`. Smoothing the change avoids clicks while a
+    /// sound moves.
+    ///
+    /// Calling this again with positions that result in the same volumes does nothing, so it
+    /// is fine to call it every frame even when nothing moved. Other positions start a new
+    /// glide from wherever the current one is.
+    pub fn set_positions_smooth(
+        &mut self,
+        emitter_pos: [f32; 3],
+        left_ear: [f32; 3],
+        right_ear: [f32; 3],
+        smoothing: Duration,
+    ) {
+        self.positions = [emitter_pos, left_ear, right_ear];
+        let target = ear_volumes(emitter_pos, left_ear, right_ear, &self.distance_model);
+        if target == self.volumes.each_ref().map(|volume| volume.target()) {
+            return;
+        }
+
+        let frame_rate = self.input.sample_rate();
+        for (volume, target) in self.volumes.iter_mut().zip(target) {
+            // stop the current glide so the new one starts where it is
+            volume.set_value(volume.value());
+            volume.set_update_rate(frame_rate);
+            volume.set_time(smoothing);
+            volume.set_target(target);
+        }
+        self.apply_volumes();
+    }
+
+    /// Returns how the volume falls off with distance.
+    #[inline]
+    pub fn distance_model(&self) -> DistanceModel {
+        self.distance_model
+    }
+
+    /// Sets how the volume falls off with distance. The volumes change right away.
+    pub fn set_distance_model(&mut self, model: DistanceModel) {
+        if model == self.distance_model {
+            return;
+        }
+        self.distance_model = model;
+        let [emitter_pos, left_ear, right_ear] = self.positions;
+        self.set_positions(emitter_pos, left_ear, right_ear);
+    }
+
+    /// Returns true while gliding to the volumes of the last set positions.
+    #[inline]
+    pub fn is_gliding(&self) -> bool {
+        self.volumes.iter().any(SmoothedParam::is_smoothing)
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+
+    fn apply_volumes(&mut self) {
+        self.input.set_volume(0, self.volumes[0].value());
+        self.input.set_volume(1, self.volumes[1].value());
     }
 }
 
@@ -107,6 +237,12 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 && self.is_gliding() {
+            self.volumes[0].next_value();
+            self.volumes[1].next_value();
+            self.apply_volumes();
+        }
+        self.channel = (self.channel + 1) % 2;
         self.input.next()
     }
 
@@ -152,4 +288,134 @@ where
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{DistanceModel, Spatial};
+
+    const LEFT_EAR: [f32; 3] = [-1.0, 0.0, 0.0];
+    const RIGHT_EAR: [f32; 3] = [1.0, 0.0, 0.0];
+
+    fn spatial() -> Spatial<SamplesBuffer<f32>> {
+        let input = SamplesBuffer::new(1, 1000, vec![1.0f32; 1000]);
+        Spatial::new(input, [-2.0, 0.0, 0.0], LEFT_EAR, RIGHT_EAR)
+    }
+
+    #[test]
+    fn starts_at_the_given_position() {
+        let mut source = spatial();
+        let left = source.next().unwrap();
+        let right = source.next().unwrap();
+        assert!(left > right);
+        assert!(!source.is_gliding());
+    }
+
+    #[test]
+    fn glides_to_new_position() {
+        let mut source = spatial();
+        source.set_positions_smooth(
+            [2.0, 0.0, 0.0],
+            LEFT_EAR,
+            RIGHT_EAR,
+            Duration::from_millis(10),
+        );
+        assert!(source.is_gliding());
+
+        let samples: Vec<f32> = source.by_ref().take(2 * 10).collect();
+        for frame in samples.windows(4).step_by(2) {
+            // left gets quieter, right louder
+            assert!(frame[2] < frame[0]);
+            assert!(frame[3] > frame[1]);
+        }
+        assert!(!source.is_gliding());
+
+        let left = source.next().unwrap();
+        let right = source.next().unwrap();
+        assert!(right > left);
+    }
+
+    #[test]
+    fn setting_the_same_position_is_idempotent() {
+        let smoothing = Duration::from_millis(10);
+        let mut source = spatial();
+        source.set_positions_smooth([2.0, 0.0, 0.0], LEFT_EAR, RIGHT_EAR, smoothing);
+        let mut reference = source.clone();
+
+        let mut samples = Vec::new();
+        for _ in 0..10 {
+            source.set_positions_smooth([2.0, 0.0, 0.0], LEFT_EAR, RIGHT_EAR, smoothing);
+            samples.push(source.next().unwrap());
+            samples.push(source.next().unwrap());
+        }
+        let expected: Vec<f32> = reference.by_ref().take(20).collect();
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn default_distance_model_is_inverse_square() {
+        let model = DistanceModel::default();
+        assert_eq!(model.gain(0.5), 1.0);
+        assert_eq!(model.gain(1.0), 1.0);
+        assert!((model.gain(2.0) - 0.25).abs() < 1e-6);
+        assert!((model.gain(10.0) - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn distance_model_parameters() {
+        let model = DistanceModel {
+            reference: 2.0,
+            max: 8.0,
+            rolloff: 1.0,
+        };
+        assert_eq!(model.gain(1.0), 1.0);
+        assert_eq!(model.gain(4.0), 0.5);
+        assert_eq!(model.gain(8.0), 0.25);
+        // no quieter beyond the maximum distance
+        assert_eq!(model.gain(100.0), 0.25);
+
+        let no_rolloff = DistanceModel {
+            rolloff: 0.0,
+            ..model
+        };
+        assert_eq!(no_rolloff.gain(100.0), 1.0);
+    }
+
+    #[test]
+    fn distance_model_changes_volumes() {
+        let mut source = spatial();
+        source.next();
+        let right = source.next().unwrap();
+
+        // the right ear is 3 away from the emitter, within the new reference distance
+        source.set_distance_model(DistanceModel {
+            reference: 10.0,
+            ..DistanceModel::default()
+        });
+        source.next();
+        assert!(source.next().unwrap() > right * 8.0);
+    }
 }
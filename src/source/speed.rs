@@ -1,30 +1,69 @@
 use std::time::Duration;
 
+use crate::source::SmoothedParam;
 use crate::{Sample, Source};
 
 use super::SeekError;
 
 /// Internal function that builds a `Speed` object.
 pub fn speed<I>(input: I, factor: f32) -> Speed<I> {
-    Speed { input, factor }
+    Speed {
+        input,
+        factor: SmoothedParam::linear(factor, Duration::ZERO, GLIDE_STEPS_PER_SEC),
+        glide_clock: 0.0,
+    }
 }
 
 /// Filter that modifies each sample by a given value.
 #[derive(Clone, Debug)]
 pub struct Speed<I> {
     input: I,
-    factor: f32,
+    factor: SmoothedParam,
+    // Playback time since the glide of the factor last advanced, in seconds.
+    glide_clock: f32,
 }
 
+// The factor glides in small steps measured in playback time, as the number of samples played
+// per second changes with the factor. The new factor only becomes audible at the next frame
+// boundary anyway.
+const GLIDE_STEPS_PER_SEC: u32 = 1000;
+
 impl<I> Speed<I>
 where
     I: Source,
     I::Item: Sample,
 {
     /// Modifies the speed factor.
+    ///
+    /// Stops a glide started by [`set_factor_smooth`](Speed::set_factor_smooth).
     #[inline]
     pub fn set_factor(&mut self, factor: f32) {
-        self.factor = factor;
+        self.factor.set_value(factor);
+    }
+
+    /// Glides the speed factor linearly from its current value to `target` over `duration`.
+    ///
+    /// The duration is in playback time, so a glide towards zero (a record slowing down to a
+    /// stop) takes `duration` to complete even though less and less of the source is played.
+    #[inline]
+    pub fn set_factor_smooth(&mut self, target: f32, duration: Duration) {
+        self.factor.set_value(self.factor.value());
+        self.factor.set_time(duration);
+        self.factor.set_target(target);
+        self.glide_clock = 0.0;
+    }
+
+    /// Returns the current speed factor.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor.value()
+    }
+
+    /// Returns true while a glide started by [`set_factor_smooth`](Speed::set_factor_smooth)
+    /// has not reached its target yet.
+    #[inline]
+    pub fn is_gliding(&self) -> bool {
+        self.factor.is_smoothing()
     }
 
     /// Returns a reference to the inner source.
@@ -55,6 +94,15 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
+        if self.factor.is_smoothing() {
+            let samples_per_sec = self.sample_rate() as f32 * self.input.channels() as f32;
+            self.glide_clock += 1.0 / samples_per_sec;
+            let step = 1.0 / GLIDE_STEPS_PER_SEC as f32;
+            while self.glide_clock >= step && self.factor.is_smoothing() {
+                self.glide_clock -= step;
+                self.factor.next_value();
+            }
+        }
         self.input.next()
     }
 
@@ -88,12 +136,28 @@ where
 
     #[inline]
     fn sample_rate(&self) -> u32 {
-        (self.input.sample_rate() as f32 * self.factor) as u32
+        // A rate of zero would make resampling divide by zero. A factor that close to zero
+        // is inaudible anyway.
+        let rate = (self.input.sample_rate() as f32 * self.factor.value()) as u32;
+        rate.max(1)
     }
 
+    /// The duration the input takes at the current factor, a faster source ends sooner.
+    /// `None` at a factor of zero, where the source never ends.
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
-        self.input.total_duration().map(|d| d.mul_f32(self.factor))
+        let factor = self.factor.value() as f64;
+        if factor <= 0.0 {
+            return None;
+        }
+        let duration = self.input.total_duration()?;
+        Duration::try_from_secs_f64(duration.as_secs_f64() / factor).ok()
+    }
+
+    /// The samples are only played faster, their number stays the same.
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        self.input.total_len()
     }
 
     #[inline]
@@ -103,7 +167,111 @@ where
          * <dvdsk noreply@davidsk.dev> */
 
         // even after 24 hours of playback f32 has enough precision
-        let pos_accounting_for_speedup = pos.mul_f32(self.factor);
+        let pos_accounting_for_speedup = pos.mul_f32(self.factor.value());
         self.input.try_seek(pos_accounting_for_speedup)
     }
+
+    /// The position the input landed on, scaled back to the sped up time.
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        let factor = self.factor.value();
+        let reached = self.input.try_seek_exact(pos.mul_f32(factor))?;
+        if factor > 0.0 {
+            Ok(reached.div_f64(factor as f64))
+        } else {
+            Ok(pos)
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    /// The samples are only played faster, the latency in samples stays the same.
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn total_duration() {
+        let inner = SamplesBuffer::new(1, 1000, vec![0i16; 2000]);
+        assert_eq!(
+            inner.clone().speed(2.0).total_duration(),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            inner.clone().speed(0.5).total_duration(),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(inner.speed(0.0).total_duration(), None);
+    }
+
+    #[test]
+    fn total_len_is_that_of_the_input() {
+        // 3 samples at 7Hz are no whole number of nanoseconds, at any speed
+        let inner = SamplesBuffer::new(1, 7, vec![0i16; 3]);
+        assert_eq!(inner.clone().speed(3.0).total_len(), Some(3));
+        assert_eq!(inner.amplify(0.5).speed(0.3).total_len(), Some(3));
+    }
+
+    #[test]
+    fn glide_to_target() {
+        let inner = SamplesBuffer::new(1, 1000, vec![0i16; 2000]);
+        let mut source = inner.speed(1.0);
+        source.set_factor_smooth(2.0, Duration::from_millis(100));
+        assert!(source.is_gliding());
+
+        let mut previous = source.sample_rate();
+        while source.is_gliding() {
+            source.next().unwrap();
+            assert!(source.sample_rate() >= previous);
+            previous = source.sample_rate();
+        }
+        assert_eq!(source.factor(), 2.0);
+        assert_eq!(source.sample_rate(), 2000);
+
+        source.set_factor_smooth(1.0, Duration::from_millis(100));
+        source.set_factor(0.5);
+        assert!(!source.is_gliding());
+        assert_eq!(source.sample_rate(), 500);
+    }
+
+    #[test]
+    fn glide_to_a_stop() {
+        let inner = SamplesBuffer::new(2, 44100, vec![0i16; 100_000]);
+        let mut source = inner.speed(1.0);
+        source.set_factor_smooth(0.0, Duration::from_secs(1));
+
+        while source.is_gliding() {
+            source.next().unwrap();
+            assert!(source.sample_rate() > 0);
+        }
+        assert_eq!(source.factor(), 0.0);
+        assert_eq!(source.sample_rate(), 1);
+    }
+
+    #[test]
+    fn try_seek_exact_scales_the_landed_position() {
+        // the buffer lands on whole frames, 20.5ms of input is 20ms
+        let mut source = SamplesBuffer::new(1, 1000, vec![0i16; 100]).speed(2.0);
+        let reached = source
+            .try_seek_exact(Duration::from_micros(10_250))
+            .unwrap();
+        assert!(reached.abs_diff(Duration::from_millis(10)) < Duration::from_micros(1));
+    }
 }
@@ -121,7 +121,7 @@ where
             }
         }
 
-        if self.remaining_duration <= self.duration_per_sample {
+        if self.remaining_duration < self.duration_per_sample {
             None
         } else if let Some(sample) = self.input.next() {
             let sample = match &self.filter {
@@ -169,21 +169,75 @@ where
         self.input.sample_rate()
     }
 
+    /// The requested duration, or that of the input if it is shorter. `None` if the length of
+    /// the input is unknown, as the input may end before the requested duration.
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
-        if let Some(duration) = self.input.total_duration() {
-            if duration < self.requested_duration {
-                Some(duration)
-            } else {
-                Some(self.requested_duration)
-            }
-        } else {
-            None
-        }
+        self.input
+            .total_duration()
+            .map(|duration| duration.min(self.requested_duration))
+    }
+
+    /// The samples that fit in the requested duration, or those of the input if it is
+    /// shorter. `None` if the length of the input is unknown.
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        let input = self.input.total_len()?;
+        let per_sample = self.duration_per_sample.as_nanos().max(1);
+        let taken = self.requested_duration.as_nanos() / per_sample;
+        Some(input.min(u64::try_from(taken).unwrap_or(u64::MAX)))
     }
 
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        self.input.try_seek_exact(pos)
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        self.input.is_seekable()
+    }
+
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        self.input.latency_samples()
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        self.input.label()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::Source;
+
+    #[test]
+    fn total_duration_matches_samples() {
+        let source =
+            SamplesBuffer::new(2, 1000, vec![0i16; 20]).take_duration(Duration::from_millis(4));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(4)));
+        assert_eq!(source.total_len(), Some(8));
+        assert_eq!(source.count(), 8);
+
+        // cut off by the end of the input
+        let source =
+            SamplesBuffer::new(1, 1000, vec![0i16; 2]).take_duration(Duration::from_secs(1));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(2)));
+        assert_eq!(source.total_len(), Some(2));
+        assert_eq!(source.count(), 2);
+
+        let source = crate::source::SineWave::new(440.0).take_duration(Duration::from_secs(1));
+        assert_eq!(source.total_duration(), None);
+        assert_eq!(source.total_len(), None);
+    }
 }
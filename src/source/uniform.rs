@@ -3,10 +3,10 @@ use std::time::Duration;
 
 use cpal::FromSample;
 
-use crate::conversions::{ChannelCountConverter, DataConverter, SampleRateConverter};
+use crate::conversions::{AntiAlias, ChannelCountConverter, DataConverter, SampleRateConverter};
 use crate::{Sample, Source};
 
-use super::SeekError;
+use super::{FormatError, SeekError};
 
 /// An iterator that reads from a `Source` and converts the samples to a
 /// specific type, sample-rate and channels count.
@@ -21,6 +21,10 @@ where
     D: Sample,
 {
     inner: Option<DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D>>,
+    // The input before the first sample is requested. The conversion is only set up then, as
+    // the format of a source might not be known up front, for example a queue that gets its
+    // first sound after being added to a mixer.
+    pending: Option<I>,
     target_channels: u16,
     target_sample_rate: u32,
     total_duration: Option<Duration>,
@@ -41,10 +45,10 @@ where
         target_sample_rate: u32,
     ) -> UniformSourceIterator<I, D> {
         let total_duration = input.total_duration();
-        let input = UniformSourceIterator::bootstrap(input, target_channels, target_sample_rate);
 
         UniformSourceIterator {
-            inner: Some(input),
+            inner: None,
+            pending: Some(input),
             target_channels,
             target_sample_rate,
             total_duration,
@@ -66,6 +70,7 @@ where
         let input = Take {
             iter: input,
             n: frame_len,
+            anti_alias: AntiAlias::new(from_sample_rate, target_sample_rate, from_channels),
         };
         let input = SampleRateConverter::new(
             input,
@@ -89,18 +94,28 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<D> {
-        if let Some(value) = self.inner.as_mut().unwrap().next() {
-            return Some(value);
-        }
+        let input = match self.pending.take() {
+            Some(input) => input,
+            None => {
+                if let Some(value) = self.inner.as_mut()?.next() {
+                    return Some(value);
+                }
 
-        let input = self
-            .inner
-            .take()
-            .unwrap()
-            .into_inner()
-            .into_inner()
-            .into_inner()
-            .iter;
+                self.inner
+                    .take()
+                    .unwrap()
+                    .into_inner()
+                    .into_inner()
+                    .into_inner()
+                    .iter
+            }
+        };
+
+        // there is no converting from zero channels or a zero sample rate, end the source
+        // instead of dividing by zero
+        if FormatError::check(&input).is_err() {
+            return None;
+        }
 
         let mut input =
             UniformSourceIterator::bootstrap(input, self.target_channels, self.target_sample_rate);
@@ -112,7 +127,10 @@ where
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.inner.as_ref().unwrap().size_hint().0, None)
+        match &self.inner {
+            Some(inner) => (inner.size_hint().0, None),
+            None => (0, None),
+        }
     }
 }
 
@@ -144,7 +162,9 @@ where
 
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        if let Some(input) = self.inner.as_mut() {
+        if let Some(input) = self.pending.as_mut() {
+            input.try_seek(pos)
+        } else if let Some(input) = self.inner.as_mut() {
             input
                 .inner_mut()
                 .inner_mut()
@@ -155,15 +175,79 @@ where
             Ok(())
         }
     }
+
+    #[inline]
+    fn try_seek_exact(&mut self, pos: Duration) -> Result<Duration, SeekError> {
+        if let Some(input) = self.pending.as_mut() {
+            input.try_seek_exact(pos)
+        } else if let Some(input) = self.inner.as_mut() {
+            input
+                .inner_mut()
+                .inner_mut()
+                .inner_mut()
+                .inner_mut()
+                .try_seek_exact(pos)
+        } else {
+            Ok(pos)
+        }
+    }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        if let Some(input) = self.pending.as_ref() {
+            input.is_seekable()
+        } else if let Some(input) = self.inner.as_ref() {
+            input.inner().inner().inner().inner().is_seekable()
+        } else {
+            false
+        }
+    }
+
+    /// The latency of the input, converted to the target sample rate.
+    #[inline]
+    fn latency_samples(&self) -> usize {
+        let input = match (&self.pending, &self.inner) {
+            (Some(input), _) => input,
+            (None, Some(inner)) => inner.inner().inner().inner().inner(),
+            (None, None) => return 0,
+        };
+        let latency = input.latency_samples() as u64;
+        if latency == 0 || input.sample_rate() == 0 {
+            return latency as usize;
+        }
+        (latency * self.target_sample_rate as u64).div_ceil(input.sample_rate() as u64) as usize
+    }
+
+    #[inline]
+    fn label(&self) -> Option<&str> {
+        match (&self.pending, &self.inner) {
+            (Some(input), _) => input.label(),
+            (None, Some(inner)) => inner.inner().inner().inner().inner().label(),
+            (None, None) => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
-struct Take<I> {
+struct Take<I>
+where
+    I: Iterator,
+{
     iter: I,
     n: Option<usize>,
+    // Filters the input before a heavy downsampling.
+    anti_alias: Option<AntiAlias<I::Item>>,
 }
 
-impl<I> Take<I> {
+impl<I> Take<I>
+where
+    I: Iterator,
+{
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.iter
+    }
+
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
         &mut self.iter
@@ -173,21 +257,23 @@ impl<I> Take<I> {
 impl<I> Iterator for Take<I>
 where
     I: Iterator,
+    I::Item: Sample,
 {
     type Item = <I as Iterator>::Item;
 
     #[inline]
     fn next(&mut self) -> Option<<I as Iterator>::Item> {
         if let Some(n) = &mut self.n {
-            if *n != 0 {
-                *n -= 1;
-                self.iter.next()
-            } else {
-                None
+            if *n == 0 {
+                return None;
             }
-        } else {
-            self.iter.next()
+            *n -= 1;
         }
+        let sample = self.iter.next()?;
+        Some(match &mut self.anti_alias {
+            Some(anti_alias) => anti_alias.filter(sample),
+            None => sample,
+        })
     }
 
     #[inline]
@@ -209,4 +295,40 @@ where
     }
 }
 
-impl<I> ExactSizeIterator for Take<I> where I: ExactSizeIterator {}
+impl<I> ExactSizeIterator for Take<I>
+where
+    I: ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::UniformSourceIterator;
+
+    fn tone(freq: f32, rate: u32) -> SamplesBuffer<f32> {
+        let samples = (0..rate)
+            .map(|n| (TAU * freq * n as f32 / rate as f32).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, rate, samples)
+    }
+
+    fn peak_after_settling(freq: f32) -> f32 {
+        let converted: UniformSourceIterator<_, f32> =
+            UniformSourceIterator::new(tone(freq, 48000), 1, 8000);
+        converted
+            .skip(100)
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn heavy_downsampling_is_anti_aliased() {
+        // 20 kHz can not be represented at 8 kHz, it would fold back to 4 kHz
+        assert!(peak_after_settling(20_000.0) < 0.1);
+        // well below the new Nyquist frequency the tone passes
+        assert!(peak_after_settling(500.0) > 0.95);
+    }
+}
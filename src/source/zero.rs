@@ -5,6 +5,16 @@ use crate::{Sample, Source};
 
 use super::SeekError;
 
+/// Builds a source that plays silence for `duration` and then ends.
+///
+/// The duration is rounded to the nearest whole frame. Useful as a spacer between sources
+/// in a [`Sink`](crate::Sink) or in a sequence built with [`from_iter`](super::from_iter).
+pub fn silence<S>(duration: Duration, channels: u16, sample_rate: u32) -> Zero<S> {
+    let frames = (duration.as_nanos() * sample_rate as u128 + 500_000_000) / 1_000_000_000;
+    let num_samples = frames as usize * channels as usize;
+    Zero::new_samples(channels, sample_rate, num_samples)
+}
+
 /// An source that produces samples with value zero (silence). Depending on if
 /// it where created with [`Zero::new`] or [`Zero::new_samples`] it can be never
 /// ending or finite.
@@ -13,6 +23,7 @@ pub struct Zero<S> {
     channels: u16,
     sample_rate: u32,
     num_samples: Option<usize>,
+    total_samples: Option<usize>,
     marker: PhantomData<S>,
 }
 
@@ -24,6 +35,7 @@ impl<S> Zero<S> {
             channels,
             sample_rate,
             num_samples: None,
+            total_samples: None,
             marker: PhantomData,
         }
     }
@@ -34,6 +46,7 @@ impl<S> Zero<S> {
             channels,
             sample_rate,
             num_samples: Some(num_samples),
+            total_samples: Some(num_samples),
             marker: PhantomData,
         }
     }
@@ -58,6 +71,14 @@ where
             Some(S::zero_value())
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.num_samples {
+            Some(num_samples) => (num_samples, Some(num_samples)),
+            None => (usize::MAX, None),
+        }
+    }
 }
 
 impl<S> Source for Zero<S>
@@ -81,11 +102,60 @@ where
 
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
-        None
+        let frames = (self.total_samples? as u64).checked_div(self.channels as u64)?;
+        Some(Duration::from_nanos(
+            (frames * 1_000_000_000).checked_div(self.sample_rate as u64)?,
+        ))
     }
 
     #[inline]
-    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if let Some(total_samples) = self.total_samples {
+            let frames = pos.as_nanos() * self.sample_rate as u128 / 1_000_000_000;
+            let samples = (frames as usize).saturating_mul(self.channels as usize);
+            self.num_samples = Some(total_samples.saturating_sub(samples));
+        }
         Ok(())
     }
+
+    #[inline]
+    fn is_seekable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::source::{silence, Source, Zero};
+
+    #[test]
+    fn silence_has_exact_length() {
+        let source: Zero<i16> = silence(Duration::from_millis(250), 2, 44100);
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), 44100);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(250)));
+        assert_eq!(source.current_frame_len(), Some(2 * 11025));
+
+        let samples: Vec<i16> = source.collect();
+        assert_eq!(samples.len(), 2 * 11025);
+        assert!(samples.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn silence_seek() {
+        let mut source: Zero<f32> = silence(Duration::from_secs(1), 1, 1000);
+        source.try_seek(Duration::from_millis(600)).unwrap();
+        assert_eq!(source.by_ref().count(), 400);
+
+        source.try_seek(Duration::from_secs(2)).unwrap();
+        assert_eq!(source.next(), None);
+    }
+
+    #[test]
+    fn infinite_zero_has_no_duration() {
+        let source: Zero<f32> = Zero::new(1, 1000);
+        assert_eq!(source.total_duration(), None);
+    }
 }
@@ -1,28 +1,125 @@
 use std::f32;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use cpal::FromSample;
 
-use crate::source::{SeekError, Spatial};
+use crate::source::{DistanceModel, SeekError, Spatial};
 use crate::stream::{OutputStreamHandle, PlayError};
-use crate::{Sample, Sink, Source};
+use crate::{Sample, Sink, SoundToken, Source};
 
 use crossbeam::atomic::AtomicCell;
 
 /// A sink that allows changing the position of the source and the listeners
 /// ears while playing. The sources played are then transformed to give a simple
 /// spatial effect. See [`Spatial`] for details.
+///
+/// Several sinks can share one [`SpatialListener`], moving it updates all of them.
 pub struct SpatialSink {
     sink: Sink,
     positions: Arc<Mutex<SoundPositions>>,
     positions_dirty: Arc<AtomicCell<bool>>,
+    listener: SpatialListener,
 }
 
 struct SoundPositions {
     emitter_position: [f32; 3],
-    left_ear: [f32; 3],
-    right_ear: [f32; 3],
+    smoothing: Duration,
+    distance_model: DistanceModel,
+}
+
+// How long it takes for a change of position to be fully audible by default. Changing the
+// volumes gradually avoids clicks.
+const DEFAULT_SMOOTHING: Duration = Duration::from_micros(16_667);
+
+/// The position of the ears that [`SpatialSink`]s are heard with.
+///
+/// This is a cheap to clone handle, all clones refer to the same listener. Give it to several
+/// sinks with [`SpatialSink::try_new_with_listener`] to move the listener of all of them at
+/// once.
+#[derive(Clone)]
+pub struct SpatialListener(Arc<ListenerShared>);
+
+struct ListenerShared {
+    ears: Mutex<([f32; 3], [f32; 3])>,
+    // Incremented on every change so sinks can tell their copy of the ears is stale.
+    version: AtomicUsize,
+}
+
+impl SpatialListener {
+    /// Builds a listener with its ears at the given positions.
+    pub fn new(left_ear: [f32; 3], right_ear: [f32; 3]) -> SpatialListener {
+        SpatialListener(Arc::new(ListenerShared {
+            ears: Mutex::new((left_ear, right_ear)),
+            version: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Returns the positions of the left and right ear.
+    pub fn ears(&self) -> ([f32; 3], [f32; 3]) {
+        *self.0.ears.lock().unwrap()
+    }
+
+    /// Moves both ears. All sinks using this listener pick up the change.
+    pub fn set_ears(&self, left_ear: [f32; 3], right_ear: [f32; 3]) {
+        *self.0.ears.lock().unwrap() = (left_ear, right_ear);
+        self.0.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Places the head of the listener at `position` facing towards `forward`, with `up`
+    /// pointing to the top of the head. The distance between the ears is kept.
+    ///
+    /// `forward` and `up` do not need to be normalized. If they are parallel, or one of them is
+    /// zero, there is no way to tell left from right and the listener stays where it was.
+    pub fn set_transform(&self, position: [f32; 3], forward: [f32; 3], up: [f32; 3]) {
+        let right = cross(forward, up);
+        let len = distance(right, [0.0; 3]);
+        if len <= f32::EPSILON {
+            return;
+        }
+
+        let mut ears = self.0.ears.lock().unwrap();
+        let (left_ear, right_ear) = *ears;
+        let half_width = distance(left_ear, right_ear) / 2.0;
+        let offset = right.map(|c| c / len * half_width);
+
+        *ears = (
+            [0, 1, 2].map(|i| position[i] - offset[i]),
+            [0, 1, 2].map(|i| position[i] + offset[i]),
+        );
+        self.0.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn set_left_ear(&self, pos: [f32; 3]) {
+        self.0.ears.lock().unwrap().0 = pos;
+        self.0.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn set_right_ear(&self, pos: [f32; 3]) {
+        self.0.ears.lock().unwrap().1 = pos;
+        self.0.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn version(&self) -> usize {
+        self.0.version.load(Ordering::Acquire)
+    }
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
 }
 
 impl SpatialSink {
@@ -33,42 +130,102 @@ impl SpatialSink {
         left_ear: [f32; 3],
         right_ear: [f32; 3],
     ) -> Result<SpatialSink, PlayError> {
+        let listener = SpatialListener::new(left_ear, right_ear);
+        SpatialSink::try_new_with_listener(stream, emitter_position, &listener)
+    }
+
+    /// Builds a new `SpatialSink` that is heard by `listener`.
+    ///
+    /// Moving the listener, or its ears through any sink that uses it, moves it for all
+    /// sinks built with it.
+    pub fn try_new_with_listener(
+        stream: &OutputStreamHandle,
+        emitter_position: [f32; 3],
+        listener: &SpatialListener,
+    ) -> Result<SpatialSink, PlayError> {
+        Ok(SpatialSink::with_sink(
+            Sink::try_new(stream)?,
+            emitter_position,
+            listener,
+        ))
+    }
 
+    fn with_sink(
+        sink: Sink,
+        emitter_position: [f32; 3],
+        listener: &SpatialListener,
+    ) -> SpatialSink {
         // Verify that we can get lock free capability for the positions_dirty flag.
-        assert_eq!(AtomicCell::<bool>::is_lock_free(), true);
+        assert!(AtomicCell::<bool>::is_lock_free());
 
-        Ok(SpatialSink {
-            sink: Sink::try_new(stream)?,
+        SpatialSink {
+            sink,
             positions: Arc::new(Mutex::new(SoundPositions {
                 emitter_position,
-                left_ear,
-                right_ear,
+                smoothing: DEFAULT_SMOOTHING,
+                distance_model: DistanceModel::default(),
             })),
             positions_dirty: Arc::new(AtomicCell::new(false)),
-        })
+            listener: listener.clone(),
+        }
+    }
+
+    /// Returns the listener this sink is heard by.
+    pub fn listener(&self) -> &SpatialListener {
+        &self.listener
     }
 
     /// Sets the position of the sound emitter in 3 dimensional space.
+    ///
+    /// The volumes glide to the new position over a few milliseconds. Setting the position
+    /// the emitter is already at has no effect, so this can be called every frame.
     pub fn set_emitter_position(&self, pos: [f32; 3]) {
-        self.positions.lock().unwrap().emitter_position = pos;
+        self.set_emitter_position_smooth(pos, DEFAULT_SMOOTHING);
+    }
+
+    /// Sets the position of the sound emitter in 3 dimensional space, gliding to it over
+    /// `smoothing`.
+    ///
+    /// Setting the position the emitter is already at has no effect, the glide in progress
+    /// continues undisturbed. See [`Spatial::set_positions_smooth`].
+    pub fn set_emitter_position_smooth(&self, pos: [f32; 3], smoothing: Duration) {
+        let mut positions = self.positions.lock().unwrap();
+        positions.emitter_position = pos;
+        positions.smoothing = smoothing;
+        self.positions_dirty.store(true);
+    }
+
+    /// Returns how the volume falls off with the distance to the listener.
+    pub fn distance_model(&self) -> DistanceModel {
+        self.positions.lock().unwrap().distance_model
+    }
+
+    /// Sets how the volume falls off with the distance to the listener, see
+    /// [`DistanceModel`]. The volumes change right away.
+    pub fn set_distance_model(&self, model: DistanceModel) {
+        self.positions.lock().unwrap().distance_model = model;
         self.positions_dirty.store(true);
     }
 
     /// Sets the position of the left ear in 3 dimensional space.
+    ///
+    /// This moves the ear of the [`listener`](SpatialSink::listener), which might be shared
+    /// with other sinks.
     pub fn set_left_ear_position(&self, pos: [f32; 3]) {
-        self.positions.lock().unwrap().left_ear = pos;
-        self.positions_dirty.store(true);
+        self.listener.set_left_ear(pos);
     }
 
     /// Sets the position of the right ear in 3 dimensional space.
+    ///
+    /// This moves the ear of the [`listener`](SpatialSink::listener), which might be shared
+    /// with other sinks.
     pub fn set_right_ear_position(&self, pos: [f32; 3]) {
-        self.positions.lock().unwrap().right_ear = pos;
-        self.positions_dirty.store(true);
+        self.listener.set_right_ear(pos);
     }
 
-    /// Appends a sound to the queue of sounds to play.
+    /// Appends a sound to the queue of sounds to play, see [`Sink::append`].
     #[inline]
-    pub fn append<S>(&self, source: S)
+    pub fn append<S>(&self, source: S) -> SoundToken
     where
         S: Source + Send + 'static,
         f32: FromSample<S::Item>,
@@ -76,33 +233,35 @@ impl SpatialSink {
     {
         let positions = self.positions.clone();
         let dirty_flag = self.positions_dirty.clone();
-        let pos_lock = self.positions.lock().unwrap();
+        let listener = self.listener.clone();
 
-        let mut current_req = SoundPositions {
-            emitter_position: pos_lock.emitter_position,
-            left_ear: pos_lock.left_ear,
-            right_ear: pos_lock.right_ear
+        let mut listener_version = listener.version();
+        let (left_ear, right_ear) = listener.ears();
+        let (emitter_position, distance_model) = {
+            let pos = positions.lock().unwrap();
+            (pos.emitter_position, pos.distance_model)
         };
 
-        let source = Spatial::new(
-            source,
-            pos_lock.emitter_position,
-            pos_lock.left_ear,
-            pos_lock.right_ear,
-        )
-        .periodic_access(Duration::from_micros(2082), move |i| {
-            let dirty = dirty_flag.load();
-            if dirty {
+        let mut source = Spatial::new(source, emitter_position, left_ear, right_ear);
+        source.set_distance_model(distance_model);
+        let source = source.periodic_access(Duration::from_micros(2082), move |i| {
+            // clear the flag before reading so updates made meanwhile are not lost
+            let moved = dirty_flag.swap(false);
+            let version = listener.version();
+            if moved || version != listener_version {
+                listener_version = version;
+                let (left_ear, right_ear) = listener.ears();
                 let pos = positions.lock().unwrap();
-                current_req.emitter_position = pos.emitter_position;
-                current_req.left_ear = pos.left_ear;
-                current_req.right_ear = pos.right_ear;
-                i.reset_lerp();
-                dirty_flag.store(false);
+                i.set_distance_model(pos.distance_model);
+                let smoothing = if moved {
+                    pos.smoothing
+                } else {
+                    DEFAULT_SMOOTHING
+                };
+                i.set_positions_smooth(pos.emitter_position, left_ear, right_ear, smoothing);
             }
-            i.set_positions(current_req.emitter_position, current_req.left_ear, current_req.right_ear);
         });
-        self.sink.append(source);
+        self.sink.append(source)
     }
 
     // Gets the volume of the sound.
@@ -143,7 +302,8 @@ impl SpatialSink {
 
     /// Resumes playback of a paused sound.
     ///
-    /// No effect if not paused.
+    /// Also cancels a fade out started with [`stop_after_fade`](SpatialSink::stop_after_fade).
+    /// No other effect if not paused.
     #[inline]
     pub fn play(&self) {
         self.sink.play();
@@ -179,6 +339,25 @@ impl SpatialSink {
         self.sink.stop()
     }
 
+    /// Gives the sink a name to tell it apart while debugging, see [`Sink::set_label`].
+    #[inline]
+    pub fn set_label(&self, label: &str) {
+        self.sink.set_label(label)
+    }
+
+    /// Returns the name given with [`set_label`](SpatialSink::set_label), if any.
+    #[inline]
+    pub fn label(&self) -> Option<String> {
+        self.sink.label()
+    }
+
+    /// Fades the sound out over `duration`, then stops the sink, see
+    /// [`Sink::stop_after_fade`].
+    #[inline]
+    pub fn stop_after_fade(&self, duration: Duration) {
+        self.sink.stop_after_fade(duration)
+    }
+
     /// Destroys the sink without stopping the sounds that are still playing.
     #[inline]
     pub fn detach(self) {
@@ -238,3 +417,67 @@ impl SpatialSink {
         self.sink.get_pos()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::queue::SourcesQueueOutput;
+    use crate::{Sink, SpatialListener, SpatialSink};
+
+    fn spatial_sink(listener: &SpatialListener) -> (SpatialSink, SourcesQueueOutput<f32>) {
+        let (sink, queue_rx) = Sink::new_idle();
+        let sink = SpatialSink::with_sink(sink, [-2.0, 0.0, 0.0], listener);
+        sink.append(SamplesBuffer::new(1, 48000, vec![1.0f32; 48000]));
+        (sink, queue_rx)
+    }
+
+    /// Plays for a while and returns the volumes of the left and right channel.
+    fn settle(queue_rx: &mut SourcesQueueOutput<f32>) -> (f32, f32) {
+        let mut frame = (0.0, 0.0);
+        for _ in 0..4800 {
+            frame = (queue_rx.next().unwrap(), queue_rx.next().unwrap());
+        }
+        frame
+    }
+
+    #[test]
+    fn shared_listener_moves_all_sinks() {
+        let listener = SpatialListener::new([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let (_sink1, mut rx1) = spatial_sink(&listener);
+        let (_sink2, mut rx2) = spatial_sink(&listener);
+
+        let (left, right) = settle(&mut rx1);
+        assert!(left > right);
+        let (left, right) = settle(&mut rx2);
+        assert!(left > right);
+
+        // turn around, the emitter is now on the right
+        listener.set_transform([0.0; 3], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]);
+        let (left_ear, right_ear) = listener.ears();
+        assert_eq!(left_ear, [1.0, 0.0, 0.0]);
+        assert_eq!(right_ear, [-1.0, 0.0, 0.0]);
+
+        let (left, right) = settle(&mut rx1);
+        assert!(right > left);
+        let (left, right) = settle(&mut rx2);
+        assert!(right > left);
+    }
+
+    #[test]
+    fn ear_setter_moves_shared_listener() {
+        let listener = SpatialListener::new([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let (sink1, _rx1) = spatial_sink(&listener);
+        let (sink2, _rx2) = spatial_sink(&listener);
+
+        sink1.set_left_ear_position([-3.0, 0.0, 0.0]);
+        assert_eq!(sink2.listener().ears().0, [-3.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn parallel_transform_keeps_the_ears() {
+        let listener = SpatialListener::new([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        listener.set_transform([5.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 2.0, 0.0]);
+        listener.set_transform([5.0, 0.0, 0.0], [0.0; 3], [0.0, 1.0, 0.0]);
+        assert_eq!(listener.ears(), ([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+    }
+}
@@ -23,6 +23,7 @@ where
     S: 'static,
 {
     data: SliceIter<'static, S>,
+    len: u64,
     channels: u16,
     sample_rate: u32,
     duration: Duration,
@@ -55,6 +56,7 @@ where
 
         StaticSamplesBuffer {
             data: data.iter(),
+            len: data.len() as u64,
             channels,
             sample_rate,
             duration,
@@ -86,6 +88,11 @@ where
         Some(self.duration)
     }
 
+    #[inline]
+    fn total_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
         Err(SeekError::NotSupported {
@@ -111,6 +118,8 @@ where
     }
 }
 
+impl<S> ExactSizeIterator for StaticSamplesBuffer<S> where S: Sample + Clone {}
+
 #[cfg(test)]
 mod tests {
     use crate::source::Source;
@@ -121,6 +130,18 @@ mod tests {
         let _ = StaticSamplesBuffer::new(1, 44100, &[0i16, 0, 0, 0, 0, 0]);
     }
 
+    #[test]
+    fn len_counts_down() {
+        let mut buf = StaticSamplesBuffer::new(1, 44100, &[1i16, 2, 3]);
+        assert_eq!(buf.len(), 3);
+        buf.next();
+        assert_eq!(buf.len(), 2);
+        buf.next();
+        buf.next();
+        assert_eq!(buf.len(), 0);
+        assert_eq!(buf.next(), None);
+    }
+
     #[test]
     #[should_panic]
     fn panic_if_zero_channels() {
@@ -1,11 +1,15 @@
 use std::io::{Read, Seek};
-use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Weak};
+use std::time::{Duration, Instant};
 use std::{error, fmt};
 
 use crate::decoder;
-use crate::dynamic_mixer::{self, DynamicMixerController};
+use crate::dynamic_mixer::{
+    self, DynamicMixerController, OneShotHandle, SharedLabel, SourceHandle,
+};
 use crate::sink::Sink;
-use crate::source::Source;
+use crate::source::{FormatError, Source};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Sample, SupportedStreamConfig};
 
@@ -14,6 +18,7 @@ use cpal::{Sample, SupportedStreamConfig};
 /// If this is dropped playback will end & attached `OutputStreamHandle`s will no longer work.
 pub struct OutputStream {
     mixer: Arc<DynamicMixerController<f32>>,
+    callback: Arc<CallbackState>,
     _stream: cpal::Stream,
 }
 
@@ -43,9 +48,14 @@ impl OutputStream {
         device: &cpal::Device,
         config: SupportedStreamConfig,
     ) -> Result<(Self, OutputStreamHandle), StreamError> {
-        let (mixer, _stream) = device.try_new_output_stream_config(config)?;
+        let callback = Arc::new(CallbackState::default());
+        let (mixer, _stream) = device.try_new_output_stream_config(config, &callback)?;
         _stream.play().map_err(StreamError::PlayStreamError)?;
-        let out = Self { mixer, _stream };
+        let out = Self {
+            mixer,
+            callback,
+            _stream,
+        };
         let handle = OutputStreamHandle {
             mixer: Arc::downgrade(&out.mixer),
         };
@@ -74,20 +84,203 @@ impl OutputStream {
                 .ok_or(original_err)
         })
     }
+
+    /// Returns the time between a sample being handed to the audio device and it being
+    /// played, as measured during the most recent output callback.
+    ///
+    /// This covers the device buffer and any latency the host reports for the hardware,
+    /// which makes it useful to align visuals with what is heard. The value is refreshed on
+    /// every callback, so it follows changes to the buffer configuration.
+    ///
+    /// Returns `None` before the first callback has run or when the host does not report
+    /// playback timestamps.
+    #[inline]
+    pub fn output_latency(&self) -> Option<Duration> {
+        self.callback.latency()
+    }
+
+    /// Starts or stops measuring how long the audio callback takes, see
+    /// [`callback_stats`](OutputStream::callback_stats). Measuring is off by default, while
+    /// it is off the callback only checks whether it should measure.
+    ///
+    /// Turning it on resets the statistics. Measuring is not supported on wasm.
+    pub fn set_callback_stats_enabled(&self, enabled: bool) {
+        if enabled {
+            self.callback.reset_stats();
+        }
+        self.callback
+            .stats_enabled
+            .store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns how long the audio callback took since measuring was turned on with
+    /// [`set_callback_stats_enabled`](OutputStream::set_callback_stats_enabled).
+    ///
+    /// Helps with finding the cause of glitches and dropouts: when filling a buffer takes
+    /// longer than playing it, the device runs out of samples. Callbacks that took that long
+    /// are counted in [`CallbackStats::late_buffers`].
+    pub fn callback_stats(&self) -> CallbackStats {
+        self.callback.stats()
+    }
+
+    /// Returns the number of channels of the output device. All sources are converted to it.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.mixer.channels()
+    }
+
+    /// Sets how much to turn down the sum of all sources played on this stream before it
+    /// goes to the device, see [`Headroom`](crate::dynamic_mixer::Headroom).
+    #[inline]
+    pub fn set_headroom(&self, headroom: dynamic_mixer::Headroom) {
+        self.mixer.set_headroom(headroom);
+    }
+
+    /// Limits the number of sources mixed at the same time on this stream, see
+    /// [`DynamicMixerController::set_max_voices`]. A [`Sink`] counts as one source.
+    #[inline]
+    pub fn set_max_voices(&self, max_voices: Option<usize>) {
+        self.mixer.set_max_voices(max_voices);
+    }
+
+    /// Returns the number of sources playing on this stream, see
+    /// [`DynamicMixerController::active_sources`].
+    #[inline]
+    pub fn active_sources(&self) -> usize {
+        self.mixer.active_sources()
+    }
+
+    /// Returns true if nothing audible was played recently, see
+    /// [`DynamicMixerController::is_silent`].
+    #[inline]
+    pub fn is_silent(&self) -> bool {
+        self.mixer.is_silent()
+    }
+}
+
+/// Calls a closure whenever the default output device of the system changes, for example
+/// when headphones are plugged in or another device is picked in the system settings.
+///
+/// cpal has no event for this so the default device is polled, by name, from a background
+/// thread. The closure runs on that thread and gets the name of the new default device, or
+/// `None` when there is none. Playback is not moved by itself: an `OutputStream` can not be
+/// sent between threads on every platform, so let the thread that owns it open a new one on
+/// the new default device and play there.
+///
+/// Hosts that always report the same default device (ALSA calls it `default` and switches
+/// the device behind it) and targets without threads never call the closure.
+///
+/// Watching stops when this is dropped.
+pub struct DefaultDeviceWatcher {
+    // Dropping the sender wakes up and ends the watching thread.
+    _stop: mpsc::Sender<()>,
+}
+
+impl DefaultDeviceWatcher {
+    /// Starts watching, checking the default device every `poll_interval`.
+    pub fn new<F>(poll_interval: Duration, on_change: F) -> DefaultDeviceWatcher
+    where
+        F: FnMut(Option<String>) + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = std::thread::Builder::new()
+            .name("rodio default device watcher".into())
+            .spawn(move || {
+                let mut on_change = on_change;
+                let mut current = default_device_name();
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(poll_interval)
+                {
+                    let name = default_device_name();
+                    if name != current {
+                        current.clone_from(&name);
+                        on_change(name);
+                    }
+                }
+            });
+        #[cfg(target_arch = "wasm32")]
+        let _ = (stopped, poll_interval, on_change);
+
+        DefaultDeviceWatcher { _stop: stop }
+    }
+}
+
+fn default_device_name() -> Option<String> {
+    cpal::default_host()
+        .default_output_device()
+        .and_then(|device| device.name().ok())
 }
 
 impl OutputStreamHandle {
+    /// Returns the number of channels of the output device, see [`OutputStream::channels`].
+    pub fn channels(&self) -> Result<u16, PlayError> {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        Ok(mixer.channels())
+    }
+
+    /// Returns the mixer of the output stream.
+    #[inline]
+    pub(crate) fn mixer(&self) -> &Weak<DynamicMixerController<f32>> {
+        &self.mixer
+    }
+
     /// Plays a source with a device until it ends.
     pub fn play_raw<S>(&self, source: S) -> Result<(), PlayError>
     where
         S: Source<Item = f32> + Send + 'static,
     {
         let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
-        mixer.add(source);
+        mixer.try_add(source)?;
+        Ok(())
+    }
+
+    /// Plays a source with a device until it ends, with a priority for the voice limit, see
+    /// [`DynamicMixerController::add_with_priority`].
+    pub fn play_raw_with_priority<S>(&self, source: S, priority: i32) -> Result<(), PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer.try_add_with_priority(source, priority)?;
+        Ok(())
+    }
+
+    /// Plays a source with a device until it ends or is stopped with the returned handle,
+    /// with the least overhead, see [`SourceHandle`].
+    pub fn play_raw_with_handle<S>(&self, source: S) -> Result<SourceHandle, PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        Ok(mixer.try_add_with_handle(source)?)
+    }
+
+    /// Plays a source once, fire and forget, for short sounds like clicks and footsteps. The
+    /// source is dropped when it ends, the returned handle can stop it early or change its
+    /// volume. See [`DynamicMixerController::try_add_oneshot`].
+    pub fn play_oneshot<S>(&self, source: S) -> Result<OneShotHandle, PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        Ok(mixer.try_add_oneshot(source)?)
+    }
+
+    /// Plays a source with a label that can change while it plays.
+    pub(crate) fn play_raw_labeled<S>(&self, source: S, label: SharedLabel) -> Result<(), PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer.try_add_labeled(source, label)?;
         Ok(())
     }
 
     /// Plays a sound once. Returns a `Sink` that can be used to control the sound.
+    ///
+    /// The sound stops when the `Sink` is dropped, see
+    /// [`play_oneshot`](OutputStreamHandle::play_oneshot) to fire and forget a sound.
     pub fn play_once<R>(&self, input: R) -> Result<Sink, PlayError>
     where
         R: Read + Seek + Send + Sync + 'static,
@@ -106,6 +299,8 @@ pub enum PlayError {
     DecoderError(decoder::DecoderError),
     /// The output device was lost.
     NoDevice,
+    /// The source reports a format that can not be played.
+    InvalidFormat(FormatError),
 }
 
 impl From<decoder::DecoderError> for PlayError {
@@ -114,11 +309,18 @@ impl From<decoder::DecoderError> for PlayError {
     }
 }
 
+impl From<FormatError> for PlayError {
+    fn from(err: FormatError) -> Self {
+        Self::InvalidFormat(err)
+    }
+}
+
 impl fmt::Display for PlayError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::DecoderError(e) => e.fmt(f),
             Self::NoDevice => write!(f, "NoDevice"),
+            Self::InvalidFormat(e) => e.fmt(f),
         }
     }
 }
@@ -128,6 +330,7 @@ impl error::Error for PlayError {
         match self {
             Self::DecoderError(e) => Some(e),
             Self::NoDevice => None,
+            Self::InvalidFormat(e) => Some(e),
         }
     }
 }
@@ -174,16 +377,118 @@ impl error::Error for StreamError {
     }
 }
 
+/// Timing of the audio callback of an [`OutputStream`], see
+/// [`OutputStream::callback_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallbackStats {
+    /// The number of times the callback ran.
+    pub callbacks: u64,
+    /// The average time the callback took to fill a buffer.
+    pub average_duration: Duration,
+    /// The longest time the callback took to fill a buffer.
+    pub max_duration: Duration,
+    /// The number of buffers that took longer to fill than they take to play. Each is likely
+    /// heard as a dropout.
+    pub late_buffers: u64,
+}
+
+/// State shared between an `OutputStream` and its audio callback. Everything is atomic, the
+/// callback never waits for a lock.
+pub(crate) struct CallbackState {
+    // In nanoseconds, `UNKNOWN_LATENCY` if the host does not report it.
+    latency_nanos: AtomicU64,
+    stats_enabled: AtomicBool,
+    callbacks: AtomicU64,
+    total_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+    late_buffers: AtomicU64,
+}
+
+impl Default for CallbackState {
+    fn default() -> CallbackState {
+        CallbackState {
+            latency_nanos: AtomicU64::new(UNKNOWN_LATENCY),
+            stats_enabled: AtomicBool::new(false),
+            callbacks: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            max_nanos: AtomicU64::new(0),
+            late_buffers: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Stored as the latency when the host does not report it.
+const UNKNOWN_LATENCY: u64 = u64::MAX;
+
+impl CallbackState {
+    /// Called at the start of the callback, returns the time if it is being measured.
+    #[inline]
+    fn begin(&self, info: &cpal::OutputCallbackInfo) -> Option<Instant> {
+        let latency = playback_latency(info).map_or(UNKNOWN_LATENCY, |latency| {
+            (latency.as_nanos() as u64).min(UNKNOWN_LATENCY - 1)
+        });
+        self.latency_nanos.store(latency, Ordering::Relaxed);
+        if cfg!(target_arch = "wasm32") || !self.stats_enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(Instant::now())
+    }
+
+    /// Called at the end of the callback with the result of `begin`.
+    #[inline]
+    fn end(&self, started: Option<Instant>, samples: usize, samples_per_sec: u64) {
+        let Some(started) = started else {
+            return;
+        };
+        let nanos = started.elapsed().as_nanos() as u64;
+        let buffer_nanos = samples as u64 * 1_000_000_000 / samples_per_sec.max(1);
+
+        self.callbacks.fetch_add(1, Ordering::Relaxed);
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+        if nanos > buffer_nanos {
+            self.late_buffers.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn latency(&self) -> Option<Duration> {
+        match self.latency_nanos.load(Ordering::Relaxed) {
+            UNKNOWN_LATENCY => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    fn stats(&self) -> CallbackStats {
+        let callbacks = self.callbacks.load(Ordering::Relaxed);
+        let total_nanos = self.total_nanos.load(Ordering::Relaxed);
+        CallbackStats {
+            callbacks,
+            average_duration: Duration::from_nanos(total_nanos.checked_div(callbacks).unwrap_or(0)),
+            max_duration: Duration::from_nanos(self.max_nanos.load(Ordering::Relaxed)),
+            late_buffers: self.late_buffers.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset_stats(&self) {
+        self.callbacks.store(0, Ordering::Relaxed);
+        self.total_nanos.store(0, Ordering::Relaxed);
+        self.max_nanos.store(0, Ordering::Relaxed);
+        self.late_buffers.store(0, Ordering::Relaxed);
+    }
+}
+
 /// Extensions to `cpal::Device`
 pub(crate) trait CpalDeviceExt {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
+        callback: Arc<CallbackState>,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError>;
 
     fn try_new_output_stream_config(
         &self,
         config: cpal::SupportedStreamConfig,
+        callback: &Arc<CallbackState>,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError>;
 }
 
@@ -191,9 +496,11 @@ impl CpalDeviceExt for cpal::Device {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
+        callback: Arc<CallbackState>,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError> {
         let (mixer_tx, mut mixer_rx) =
             dynamic_mixer::mixer::<f32>(format.channels(), format.sample_rate().0);
+        let samples_per_sec = format.channels() as u64 * format.sample_rate().0 as u64;
 
         let error_callback = |err| {
             #[cfg(feature = "tracing")]
@@ -205,106 +512,129 @@ impl CpalDeviceExt for cpal::Device {
         match format.sample_format() {
             cpal::SampleFormat::F32 => self.build_output_stream::<f32, _, _>(
                 &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().unwrap_or(0f32))
+                move |data, info| {
+                    let started = callback.begin(info);
+                    mixer_rx.fill_output(data);
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::F64 => self.build_output_stream::<f64, _, _>(
                 &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0f64))
+                move |data, info| {
+                    let started = callback.begin(info);
+                    data.iter_mut().for_each(|d| {
+                        *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0f64)
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I8 => self.build_output_stream::<i8, _, _>(
                 &format.config(),
-                move |data, _| {
+                move |data, info| {
+                    let started = callback.begin(info);
                     data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i8))
+                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i8));
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I16 => self.build_output_stream::<i16, _, _>(
                 &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i16))
+                move |data, info| {
+                    let started = callback.begin(info);
+                    data.iter_mut().for_each(|d| {
+                        *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i16)
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I32 => self.build_output_stream::<i32, _, _>(
                 &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i32))
+                move |data, info| {
+                    let started = callback.begin(info);
+                    data.iter_mut().for_each(|d| {
+                        *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i32)
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I64 => self.build_output_stream::<i64, _, _>(
                 &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i64))
+                move |data, info| {
+                    let started = callback.begin(info);
+                    data.iter_mut().for_each(|d| {
+                        *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i64)
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U8 => self.build_output_stream::<u8, _, _>(
                 &format.config(),
-                move |data, _| {
+                move |data, info| {
+                    let started = callback.begin(info);
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
                             .next()
                             .map(Sample::from_sample)
                             .unwrap_or(u8::MAX / 2)
-                    })
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U16 => self.build_output_stream::<u16, _, _>(
                 &format.config(),
-                move |data, _| {
+                move |data, info| {
+                    let started = callback.begin(info);
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
                             .next()
                             .map(Sample::from_sample)
                             .unwrap_or(u16::MAX / 2)
-                    })
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U32 => self.build_output_stream::<u32, _, _>(
                 &format.config(),
-                move |data, _| {
+                move |data, info| {
+                    let started = callback.begin(info);
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
                             .next()
                             .map(Sample::from_sample)
                             .unwrap_or(u32::MAX / 2)
-                    })
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U64 => self.build_output_stream::<u64, _, _>(
                 &format.config(),
-                move |data, _| {
+                move |data, info| {
+                    let started = callback.begin(info);
                     data.iter_mut().for_each(|d| {
                         *d = mixer_rx
                             .next()
                             .map(Sample::from_sample)
                             .unwrap_or(u64::MAX / 2)
-                    })
+                    });
+                    callback.end(started, data.len(), samples_per_sec);
                 },
                 error_callback,
                 None,
@@ -317,17 +647,29 @@ impl CpalDeviceExt for cpal::Device {
     fn try_new_output_stream_config(
         &self,
         config: SupportedStreamConfig,
+        callback: &Arc<CallbackState>,
     ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError> {
-        self.new_output_stream_with_format(config).or_else(|err| {
-            // look through all supported formats to see if another works
-            supported_output_formats(self)?
-                .find_map(|format| self.new_output_stream_with_format(format).ok())
-                // return original error if nothing works
-                .ok_or(StreamError::BuildStreamError(err))
-        })
+        self.new_output_stream_with_format(config, callback.clone())
+            .or_else(|err| {
+                // look through all supported formats to see if another works
+                supported_output_formats(self)?
+                    .find_map(|format| {
+                        self.new_output_stream_with_format(format, callback.clone())
+                            .ok()
+                    })
+                    // return original error if nothing works
+                    .ok_or(StreamError::BuildStreamError(err))
+            })
     }
 }
 
+/// Time between the callback being invoked and its first sample being played, if the host
+/// reports it.
+fn playback_latency(info: &cpal::OutputCallbackInfo) -> Option<Duration> {
+    let timestamp = info.timestamp();
+    timestamp.playback.duration_since(&timestamp.callback)
+}
+
 /// All the supported output formats with sample rates
 fn supported_output_formats(
     device: &cpal::Device,
@@ -351,3 +693,34 @@ fn supported_output_formats(
         formats
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CallbackState, CallbackStats};
+
+    #[test]
+    fn callback_stats() {
+        let state = CallbackState::default();
+        assert_eq!(state.latency(), None);
+        let ago = |ms| Instant::now().checked_sub(Duration::from_millis(ms));
+
+        // 1 second worth of samples, plenty of time
+        state.end(ago(10), 48_000, 48_000);
+        // 1 millisecond worth of samples, too late
+        state.end(ago(30), 48, 48_000);
+        // not measured
+        state.end(None, 48, 48_000);
+
+        let stats = state.stats();
+        assert_eq!(stats.callbacks, 2);
+        assert_eq!(stats.late_buffers, 1);
+        assert!(stats.max_duration >= Duration::from_millis(30));
+        assert!(stats.average_duration >= Duration::from_millis(20));
+        assert!(stats.average_duration < stats.max_duration);
+
+        state.reset_stats();
+        assert_eq!(state.stats(), CallbackStats::default());
+    }
+}
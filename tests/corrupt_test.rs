@@ -0,0 +1,121 @@
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+use std::io::Cursor;
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+use std::sync::Arc;
+
+/// Decodes until the end and returns the number of samples and the number of reported errors.
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+fn decode(data: Vec<u8>) -> (usize, usize) {
+    let mut decoder = rodio::Decoder::new(Cursor::new(data)).unwrap();
+    let errors = Arc::new(AtomicUsize::new(0));
+    let errors_clone = errors.clone();
+    decoder.set_error_callback(move |count| errors_clone.store(count, Ordering::Relaxed));
+
+    let len = decoder.by_ref().count();
+    (len, errors.load(Ordering::Relaxed))
+}
+
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+fn truncated(path: &str) -> Vec<u8> {
+    let mut data = std::fs::read(path).unwrap();
+    data.truncate(data.len() / 2);
+    data
+}
+
+/// Overwrites a few kilobytes in the middle of the file with garbage.
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    all(feature = "flac", not(feature = "symphonia-flac"))
+))]
+fn corrupted(path: &str) -> Vec<u8> {
+    let mut data = std::fs::read(path).unwrap();
+    let start = data.len() / 3;
+    for (i, byte) in data[start..start + 4096].iter_mut().enumerate() {
+        *byte = (i * 37) as u8;
+    }
+    data
+}
+
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac"
+))]
+fn full_len(path: &str) -> usize {
+    decode(std::fs::read(path).unwrap()).0
+}
+
+#[cfg(feature = "mp3")]
+#[test]
+fn truncated_mp3_returns_partial_audio() {
+    let (len, _) = decode(truncated("assets/music.mp3"));
+    assert!(len > 0);
+    assert!(len < full_len("assets/music.mp3"));
+}
+
+#[cfg(feature = "mp3")]
+#[test]
+fn corrupted_mp3_keeps_playing() {
+    let (len, _) = decode(corrupted("assets/music.mp3"));
+    assert!(len > full_len("assets/music.mp3") * 9 / 10);
+}
+
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn truncated_vorbis_returns_partial_audio() {
+    let (len, _) = decode(truncated("assets/music.ogg"));
+    assert!(len > 0);
+    assert!(len < full_len("assets/music.ogg"));
+}
+
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn corrupted_vorbis_skips_bad_packets() {
+    let (len, errors) = decode(corrupted("assets/music.ogg"));
+    assert!(errors > 0);
+    assert!(len > full_len("assets/music.ogg") * 9 / 10);
+}
+
+#[cfg(feature = "flac")]
+#[test]
+fn truncated_flac_returns_partial_audio() {
+    let (len, _) = decode(truncated("assets/music.flac"));
+    assert!(len > 0);
+    assert!(len < full_len("assets/music.flac"));
+}
+
+#[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+#[test]
+fn corrupted_flac_ends_cleanly() {
+    let mut decoder = rodio::Decoder::new(Cursor::new(corrupted("assets/music.flac"))).unwrap();
+    let errors = Arc::new(AtomicUsize::new(0));
+    let errors_clone = errors.clone();
+    decoder.set_error_callback(move |count| errors_clone.store(count, Ordering::Relaxed));
+
+    assert!(decoder.by_ref().count() > 0);
+    assert_eq!(decoder.next(), None);
+    assert!(errors.load(Ordering::Relaxed) > 0);
+}
@@ -24,3 +24,36 @@ fn test_flac_encodings() {
         assert_eq!(decoder.total_duration(), Some(Duration::from_secs(3)));
     }
 }
+
+#[cfg(feature = "flac")]
+#[test]
+fn test_flac_total_len() {
+    let file = std::fs::File::open("assets/audacity16bit_level5.flac").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let total_len = rodio::Source::total_len(&decoder).unwrap();
+    assert_eq!(total_len, decoder.count() as u64);
+}
+
+#[cfg(feature = "flac")]
+#[test]
+fn test_flac_fill_buffer_matches_next() {
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/music.flac").unwrap();
+    let expected: Vec<i16> = rodio::Decoder::new(BufReader::new(file)).unwrap().collect();
+
+    let file = std::fs::File::open("assets/music.flac").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let mut decoded = Vec::new();
+    // not a multiple of the block size so chunks straddle blocks
+    let mut chunk = [0i16; 1000];
+    loop {
+        let filled = decoder.fill_buffer(&mut chunk);
+        decoded.extend_from_slice(&chunk[..filled]);
+        if filled < chunk.len() {
+            break;
+        }
+    }
+
+    assert_eq!(decoded, expected);
+}
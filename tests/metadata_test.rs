@@ -0,0 +1,76 @@
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac",
+    all(feature = "wav", not(feature = "symphonia-wav"))
+))]
+use std::io::BufReader;
+
+#[cfg(any(
+    feature = "mp3",
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "flac",
+    all(feature = "wav", not(feature = "symphonia-wav"))
+))]
+fn open(path: &str) -> rodio::Decoder<BufReader<std::fs::File>> {
+    let file = std::fs::File::open(path).unwrap();
+    rodio::Decoder::new(BufReader::new(file)).unwrap()
+}
+
+#[cfg(feature = "mp3")]
+#[test]
+fn mp3_id3_tags() {
+    let decoder = open("assets/music.mp3");
+    assert_eq!(decoder.codec(), Some("mp3"));
+    assert_eq!(decoder.bits_per_sample(), None);
+
+    let metadata = decoder.metadata();
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+    assert!(metadata
+        .tags
+        .iter()
+        .any(|(key, value)| key == "TPE1" && value == "RP and E Goldstein"));
+}
+
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn vorbis_comments() {
+    let decoder = open("assets/music.ogg");
+    assert_eq!(decoder.codec(), Some("vorbis"));
+
+    let metadata = decoder.metadata();
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+}
+
+#[cfg(feature = "flac")]
+#[test]
+fn flac_tags_and_bit_depth() {
+    let decoder = open("assets/music.flac");
+    assert_eq!(decoder.codec(), Some("flac"));
+    assert_eq!(decoder.bits_per_sample(), Some(16));
+    assert_eq!(
+        decoder.metadata().title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+
+    let decoder = open("assets/audacity24bit_level5.flac");
+    assert_eq!(decoder.bits_per_sample(), Some(24));
+    assert_eq!(decoder.metadata(), rodio::decoder::Metadata::default());
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn wav_bit_depth() {
+    let decoder = open("assets/lmms24bit.wav");
+    assert_eq!(decoder.codec(), Some("pcm"));
+    assert_eq!(decoder.bits_per_sample(), Some(24));
+    assert!(decoder.metadata().tags.is_empty());
+}
@@ -0,0 +1,101 @@
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn test_mp3_gapless_trims_delay_and_padding() {
+    use rodio::Source;
+    use std::io::BufReader;
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let gapless = rodio::Decoder::new_with_gapless(BufReader::new(file), true).unwrap();
+    let channels = gapless.channels() as usize;
+    let gapless_len = gapless.count();
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let raw = rodio::Decoder::new_with_gapless(BufReader::new(file), false).unwrap();
+    let raw_len = raw.count();
+
+    assert!(gapless_len < raw_len);
+    // the raw stream consists of whole mp3 frames
+    assert_eq!(raw_len % (1152 * channels), 0);
+}
+
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn test_mp3_fill_buffer_matches_next() {
+    use rodio::Source;
+    use std::io::BufReader;
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let expected: Vec<i16> = rodio::Decoder::new(BufReader::new(file)).unwrap().collect();
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let mut decoded = Vec::new();
+    // not a multiple of the frame size so chunks straddle packets
+    let mut chunk = [0i16; 1000];
+    loop {
+        let filled = decoder.fill_buffer(&mut chunk);
+        decoded.extend_from_slice(&chunk[..filled]);
+        if filled < chunk.len() {
+            break;
+        }
+    }
+
+    assert_eq!(decoded, expected);
+}
+
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn test_mp3_total_len() {
+    use rodio::Source;
+    use std::io::BufReader;
+
+    for gapless in [true, false] {
+        let file = std::fs::File::open("assets/music.mp3").unwrap();
+        let decoder = rodio::Decoder::new_with_gapless(BufReader::new(file), gapless).unwrap();
+        let total_len = decoder.total_len().unwrap();
+        assert_eq!(total_len, decoder.count() as u64, "gapless: {gapless}");
+    }
+}
+
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn test_mp3_seek_reports_landed_position() {
+    use rodio::Source;
+    use std::io::BufReader;
+    use std::time::Duration;
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let mut seeked = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let channels = seeked.channels() as u64;
+    let sample_rate = seeked.sample_rate() as u64;
+    let landed = seeked
+        .try_seek_exact(Duration::from_secs_f64(1.23456))
+        .unwrap();
+    // seeks land on a whole frame
+    let frame = (landed.as_secs_f64() * sample_rate as f64).round() as u64;
+    assert!(landed.abs_diff(Duration::from_secs_f64(1.23456)) < Duration::from_millis(1));
+
+    let file = std::fs::File::open("assets/music.mp3").unwrap();
+    let played = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let expected: Vec<i16> = played
+        .skip((frame * channels) as usize)
+        .skip(4096)
+        .take(1000)
+        .collect();
+    let actual: Vec<i16> = seeked.skip(4096).take(1000).collect();
+    assert_eq!(actual, expected);
+}
+
+#[cfg(feature = "symphonia-mp3")]
+#[test]
+fn test_mp3_from_memory_with_hint() {
+    use std::io::Cursor;
+
+    let data = std::fs::read("assets/music.mp3").unwrap();
+    let decoder = rodio::Decoder::new_with_hint(Cursor::new(data.clone()), "audio/mpeg").unwrap();
+    assert_eq!(decoder.codec(), Some("mp3"));
+
+    // a wrong hint falls back to probing every format
+    let decoder = rodio::Decoder::new_with_hint(Cursor::new(data), ".FLAC").unwrap();
+    assert_eq!(decoder.codec(), Some("mp3"));
+}
@@ -0,0 +1,27 @@
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+use std::io::BufReader;
+
+#[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+#[test]
+fn test_vorbis_fill_buffer_matches_next() {
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/music.ogg").unwrap();
+    let expected: Vec<i16> = rodio::Decoder::new(BufReader::new(file)).unwrap().collect();
+
+    let file = std::fs::File::open("assets/music.ogg").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let mut decoded = Vec::new();
+    // not a multiple of the packet size so chunks straddle packets
+    let mut chunk = [0i16; 1000];
+    loop {
+        let filled = decoder.fill_buffer(&mut chunk);
+        decoded.extend_from_slice(&chunk[..filled]);
+        if filled < chunk.len() {
+            break;
+        }
+    }
+
+    assert!(!expected.is_empty());
+    assert_eq!(decoded, expected);
+}
@@ -32,3 +32,221 @@ fn test_wav_encodings() {
     let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
     assert!(decoder.any(|x| x != 0));
 }
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+fn generate_wav<S: hound::Sample + Copy>(
+    bits_per_sample: u16,
+    sample_format: hound::SampleFormat,
+    samples: &[S],
+) -> std::io::Cursor<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44100,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut data = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut data, spec).unwrap();
+    for &sample in samples {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+    data.set_position(0);
+    data
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn test_wav_24bit_int_values() {
+    let data = generate_wav(
+        24,
+        hound::SampleFormat::Int,
+        &[0i32, 8_388_607, -8_388_608, 0x12_3456, -0x12_3456],
+    );
+    let decoder = rodio::Decoder::new(data).unwrap();
+    let samples: Vec<i16> = decoder.collect();
+    assert_eq!(samples, vec![0, 32767, -32768, 0x1234, -0x1235]);
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn test_wav_32bit_int_values() {
+    let data = generate_wav(
+        32,
+        hound::SampleFormat::Int,
+        &[0i32, i32::MAX, i32::MIN, 1 << 16],
+    );
+    let decoder = rodio::Decoder::new(data).unwrap();
+    let samples: Vec<i16> = decoder.collect();
+    assert_eq!(samples, vec![0, 32767, -32768, 1]);
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn test_wav_32bit_float_values() {
+    let data = generate_wav(
+        32,
+        hound::SampleFormat::Float,
+        &[0.0f32, 1.0, -1.0, 0.5, 2.0, -2.0],
+    );
+    let decoder = rodio::Decoder::new(data).unwrap();
+    let samples: Vec<i16> = decoder.collect();
+    assert_eq!(samples, vec![0, 32767, -32767, 16383, 32767, -32767]);
+}
+
+/// Appends a `smpl` chunk with a single loop to a WAV file and fixes up the RIFF size.
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+fn append_smpl_chunk(
+    data: std::io::Cursor<Vec<u8>>,
+    start: u32,
+    end: u32,
+) -> std::io::Cursor<Vec<u8>> {
+    let mut bytes = data.into_inner();
+
+    let mut chunk = vec![0u8; 36 + 24];
+    chunk[28..32].copy_from_slice(&1u32.to_le_bytes()); // number of loops
+    chunk[36 + 8..36 + 12].copy_from_slice(&start.to_le_bytes());
+    chunk[36 + 12..36 + 16].copy_from_slice(&end.to_le_bytes());
+
+    bytes.extend_from_slice(b"smpl");
+    bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&chunk);
+
+    let riff_len = bytes.len() as u32 - 8;
+    bytes[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    std::io::Cursor::new(bytes)
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn test_wav_loop_points() {
+    use std::time::Duration;
+
+    let samples = vec![0i16; 44100];
+    let data = generate_wav(16, hound::SampleFormat::Int, &samples);
+    let data = append_smpl_chunk(data, 11025, 22049);
+
+    let decoder = rodio::Decoder::new(data).unwrap();
+    assert_eq!(
+        decoder.loop_points(),
+        Some((Duration::from_millis(250), Duration::from_millis(500)))
+    );
+    assert_eq!(decoder.count(), 44100);
+}
+
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+#[test]
+fn test_wav_without_loop_points() {
+    let data = generate_wav(16, hound::SampleFormat::Int, &[1i16, 2, 3]);
+    let decoder = rodio::Decoder::new(data).unwrap();
+    assert_eq!(decoder.loop_points(), None);
+
+    let file = std::fs::File::open("assets/audacity16bit.wav").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    assert_eq!(decoder.loop_points(), None);
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_total_len() {
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/lmms16bit.wav").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let total_len = decoder.total_len().unwrap();
+    assert_eq!(total_len, decoder.count() as u64);
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_with_hint() {
+    use std::io::Cursor;
+
+    let data = std::fs::read("assets/lmms16bit.wav").unwrap();
+    let expected: Vec<i16> = rodio::Decoder::new(Cursor::new(data.clone()))
+        .unwrap()
+        .collect();
+
+    for hint in ["wav", "audio/x-wav", "mp3"] {
+        let decoder = rodio::Decoder::new_with_hint(Cursor::new(data.clone()), hint).unwrap();
+        assert_eq!(decoder.collect::<Vec<_>>(), expected, "hint: {hint}");
+    }
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_is_seekable() {
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/lmms16bit.wav").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    assert!(decoder.is_seekable());
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_from_bytes() {
+    use rodio::Source;
+    use std::time::Duration;
+
+    static BEEP: &[u8] = include_bytes!("../assets/beep.wav");
+    let mut decoder = rodio::Decoder::from_bytes(BEEP).unwrap();
+    assert!(decoder.is_seekable());
+
+    let first: Vec<i16> = decoder.by_ref().take(100).collect();
+    decoder.try_seek(Duration::ZERO).unwrap();
+    assert_eq!(decoder.by_ref().take(100).collect::<Vec<_>>(), first);
+
+    let again = rodio::Decoder::from_bytes(BEEP).unwrap();
+    assert_eq!(again.take(100).collect::<Vec<_>>(), first);
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_len_counts_down() {
+    use rodio::Source;
+    use std::time::Duration;
+
+    let file = std::fs::File::open("assets/audacity16bit.wav").unwrap();
+    let mut decoder = rodio::Decoder::new_wav(BufReader::new(file)).unwrap();
+    let total = decoder.total_len().unwrap() as usize;
+    assert_eq!(decoder.size_hint(), (total, Some(total)));
+
+    decoder.by_ref().take(1000).for_each(drop);
+    assert_eq!(decoder.size_hint(), (total - 1000, Some(total - 1000)));
+    decoder.by_ref().for_each(drop);
+    assert_eq!(decoder.size_hint(), (0, Some(0)));
+
+    decoder.try_seek(Duration::ZERO).unwrap();
+    assert_eq!(decoder.size_hint(), (total, Some(total)));
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_wav_fill_buffer_matches_next() {
+    use rodio::Source;
+
+    for asset in [
+        "assets/audacity16bit.wav",
+        "assets/lmms24bit.wav",
+        "assets/audacity32bit.wav",
+        "assets/audacity32bit_int.wav",
+    ] {
+        let file = std::fs::File::open(asset).unwrap();
+        let expected: Vec<i16> = rodio::Decoder::new(BufReader::new(file)).unwrap().collect();
+
+        let file = std::fs::File::open(asset).unwrap();
+        let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+        let mut decoded = Vec::new();
+        let mut chunk = [0i16; 1000];
+        loop {
+            let filled = decoder.fill_buffer(&mut chunk);
+            decoded.extend_from_slice(&chunk[..filled]);
+            if filled < chunk.len() {
+                break;
+            }
+        }
+
+        assert_eq!(decoded, expected, "{asset}");
+    }
+}